@@ -1,4 +1,5 @@
-
 fn main() {
+    // the standalone wz-server is built without tauri
+    #[cfg(feature = "desktop")]
     tauri_build::build()
-}
\ No newline at end of file
+}
@@ -0,0 +1,129 @@
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use maple_lens::cache::{ResponseCache, DEFAULT_IMAGE_CACHE_BYTES};
+use maple_lens::link_index::LinkIndex;
+use maple_lens::memory::ImageTracker;
+use maple_lens::server::{self, access::ServerAccess, config::ServerConfig, ServerState};
+use maple_lens::stats::SharedRuntimeStats;
+use maple_lens::{handlers, utils, MountSkillCache, PackSources, Result, StringDict};
+use wz_reader::WzNodeCast;
+
+const DEFAULT_CONFIG_PATH: &str = "wz-server.json";
+
+/// usage: wz-server [config path], the config defaults to `wz-server.json`
+#[tokio::main]
+async fn main() {
+    let config_path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_CONFIG_PATH.to_string());
+
+    if let Err(e) = run(&config_path).await {
+        eprintln!("wz-server failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(config_path: &str) -> Result<()> {
+    let config = ServerConfig::load(config_path)?;
+
+    // a folder is a dump of .img or .xml files instead of the client
+    let is_dump = Path::new(&config.base_path).is_dir();
+
+    let wz_key = match &config.region {
+        Some(region) => {
+            let selection = utils::parse_key_selection(region)?;
+            if is_dump {
+                utils::resolve_dump_key(selection)
+            } else {
+                utils::resolve_wz_key(selection, &config.base_path).await?
+            }
+        }
+        None => utils::ResolvedWzKey::default(),
+    };
+
+    let pack_sources = PackSources::default();
+
+    let options = utils::LoadOptions {
+        pack_families: config.pack_families.clone(),
+        pack_sources: pack_sources.clone(),
+        keys: wz_key.keys,
+    };
+
+    let reporter = utils::LoadReporter::silent(&config.base_path);
+    reporter.set_key(wz_key.label);
+
+    let root_node = if is_dump {
+        utils::resolve_dump(&config.base_path, wz_key.iv, reporter.clone()).await?
+    } else {
+        utils::resolve_base_with_report(
+            &config.base_path,
+            wz_key.version,
+            &options,
+            reporter.clone(),
+        )
+        .await?
+    };
+
+    let patch_version = root_node
+        .read()
+        .unwrap()
+        .try_as_file()
+        .map(|f| f.wz_file_meta.patch_version)
+        .unwrap_or(0);
+
+    let report = reporter.finish(patch_version);
+    println!(
+        "loaded {} files from {} in {}ms, {} skipped, {} failed",
+        report.loaded.len(),
+        report.path,
+        report.elapsed_ms,
+        report.skipped.len(),
+        report.failed.len(),
+    );
+    for failed in report.failed.iter() {
+        eprintln!("failed to load {}: {}", failed.path, failed.reason);
+    }
+
+    let images = ImageTracker::new(Arc::clone(&root_node));
+    if let Some(bytes) = config.get_memory_budget_bytes() {
+        images.set_budget(bytes);
+    }
+
+    let image_cache = ResponseCache::new(
+        config
+            .get_image_cache_bytes()
+            .unwrap_or(DEFAULT_IMAGE_CACHE_BYTES),
+    );
+
+    let link_index = LinkIndex::new();
+    link_index.spawn_indexer(Arc::clone(&images));
+
+    let access = match config.token.clone() {
+        Some(token) => ServerAccess::with_token(token),
+        None => ServerAccess::new(),
+    };
+    access.set_allowed_origins(config.allowed_origins.clone());
+
+    println!("access token: {}", access.get_token());
+
+    let mount_overrides = match &config.mount_skill_overrides {
+        Some(path) => handlers::load_mount_skill_overrides(Path::new(path))?,
+        None => Default::default(),
+    };
+
+    let state = ServerState {
+        node: root_node,
+        string_dict: StringDict::default(),
+        images,
+        stats: SharedRuntimeStats::default(),
+        image_cache,
+        pack_sources,
+        link_index,
+        access,
+        mount_overrides: Arc::new(RwLock::new(mount_overrides)),
+        mount_skills: MountSkillCache::default(),
+    };
+
+    server::app(state, config.get_options()).await
+}
@@ -0,0 +1,92 @@
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use axum::http::{HeaderMap, HeaderValue};
+use lru::LruCache;
+
+pub const DEFAULT_IMAGE_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Clone)]
+pub struct CachedResponse {
+    pub etag: HeaderValue,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+struct ResponseCacheInner {
+    entries: LruCache<String, CachedResponse>,
+    size: usize,
+}
+
+/// lru cache of encoded responses, limited by the total body size
+pub struct ResponseCache {
+    capacity: usize,
+    inner: Mutex<ResponseCacheInner>,
+}
+
+pub type SharedResponseCache = Arc<ResponseCache>;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// fnv-1a of the data, unlike `DefaultHasher` it is the same across builds
+fn hash_content(data: &[u8]) -> u64 {
+    data.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// the etag is the hash of the encoded body, so it survives the restart of the server
+pub fn get_content_etag(body: &[u8]) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{:016x}\"", hash_content(body))).unwrap()
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> SharedResponseCache {
+        Arc::new(ResponseCache {
+            capacity,
+            inner: Mutex::new(ResponseCacheInner {
+                entries: LruCache::unbounded(),
+                size: 0,
+            }),
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.inner.lock().unwrap().entries.get(key).cloned()
+    }
+
+    pub fn insert(&self, key: String, response: CachedResponse) {
+        // a single response larger than the cache is not worth to keep
+        if self.capacity == 0 || response.body.len() > self.capacity {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.size += response.body.len();
+        if let Some(old) = inner.entries.put(key, response) {
+            inner.size -= old.body.len();
+        }
+
+        while inner.size > self.capacity {
+            let Some((_, evicted)) = inner.entries.pop_lru() else {
+                break;
+            };
+            inner.size -= evicted.body.len();
+        }
+    }
+
+    /// (entry count, total body size)
+    pub fn get_usage(&self) -> (usize, usize) {
+        let inner = self.inner.lock().unwrap();
+        (inner.entries.len(), inner.size)
+    }
+
+    /// the cached responses are outdated once the root is replaced or extended
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.entries.clear();
+        inner.size = 0;
+    }
+}
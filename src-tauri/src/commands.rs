@@ -1,14 +1,25 @@
-use crate::{models, utils, AppStore, Error, Result}; // 移除 handlers (如果没用到)
+use crate::jobs::{ExportProgress, ExportTarget, JobSource};
+use crate::link_index::LinkReferences;
+use crate::stats;
+use crate::utils::LoadReport;
+use crate::{handlers, models, utils, AppStore, Error, PackSources, Result};
 use serde::Deserialize; 
 use serde_json::{Map, Value}; // 移除 to_string (如果没用到)
-use tauri::{command, ipc, AppHandle, Runtime, State, Window};
-use wz_reader::{util::node_util, version::WzMapleVersion, WzNodeCast};
+use std::path::{Path, PathBuf};
+use tauri::{
+    async_runtime::spawn_blocking, command, ipc, AppHandle, Emitter, Manager, Runtime, State,
+    Window,
+};
+use tauri_plugin_store::StoreExt;
+use wz_reader::{util::node_util, WzNodeCast};
 
-// 引入 WebP 编码所需的库
-use webp_animation::{Encoder, EncoderOptions};
 // 修正：直接使用 image crate，不需要 use image::self
 use image; 
 
+const MEGABYTE: u64 = 1024 * 1024;
+
+pub const INIT_PROGRESS_EVENT: &'static str = "init://progress";
+
 #[derive(Deserialize)]
 pub struct WebPFrame {
     // Rust 应该接收一个 u8 数组（来自 JS 的 Array<number> 或 Uint8Array）
@@ -16,6 +27,44 @@ pub struct WebPFrame {
     pub delay: i32,    // 延迟，单位 ms
 }
 
+/// the file an animation export is written to and its format
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationOutput {
+    pub path: String,
+    pub format: Option<handlers::AnimationFormat>,
+    pub gif_options: Option<handlers::GifOptions>,
+}
+
+impl AnimationOutput {
+    fn write(&self, animation: &handlers::ComposedAnimation) -> Result<()> {
+        let gif_options = self.gif_options.clone().unwrap_or_default();
+        let data =
+            handlers::encode_composed(animation, self.format.unwrap_or_default(), &gif_options)?;
+
+        Ok(std::fs::write(&self.path, data)?)
+    }
+}
+
+/// decode the png frames drawn by the frontend, they must have the same size
+fn load_frames(
+    frames: Vec<WebPFrame>,
+    width: u32,
+    height: u32,
+) -> Result<handlers::ComposedAnimation> {
+    let frames = frames
+        .into_iter()
+        .map(|frame| {
+            let image = image::load_from_memory(&frame.data).map_err(|e| {
+                Error::ImageProcessingError(format!("Failed to load image buffer: {}", e))
+            })?;
+            Ok((image.to_rgba8(), frame.delay))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    handlers::ComposedAnimation::from_frames(width, height, frames)
+}
+
 #[command]
 pub(crate) async fn encode_webp_anim<R: Runtime>(
     _app: AppHandle<R>,
@@ -28,44 +77,33 @@ pub(crate) async fn encode_webp_anim<R: Runtime>(
         return Ok(Vec::new());
     }
 
-    // 1. 初始化 WebP 编码器
-    let mut options = EncoderOptions::default();
-    options.anim_params.loop_count = 0;
-    
-    // 使用 ImageProcessingError
-    let mut encoder = Encoder::new_with_options((width, height), options)
-        .map_err(|e| Error::ImageProcessingError(format!("Encoder init failed: {}", e)))?; 
-
-    let mut timestamp_ms = 0;
-
-    for frame in frames {
-        // 2. 解码前端传来的 PNG buffer
-        let img = image::load_from_memory(&frame.data)
-            // 使用 ImageProcessingError
-            .map_err(|e| Error::ImageProcessingError(format!("Failed to load image buffer: {}", e)))?;
-
-        let rgba_img = img.to_rgba8(); 
-
-        // 检查尺寸是否匹配
-        if img.width() != width || img.height() != height {
-             // 对于静态字符串，也封装成 String
-             return Err(Error::ImageProcessingError("Frame size mismatch".to_string()));
-        }
-
-        // 3. 添加帧
-        encoder.add_frame(rgba_img.as_raw(), timestamp_ms)
-             // 使用 ImageProcessingError
-             .map_err(|e| Error::ImageProcessingError(format!("Add frame failed: {}", e)))?;
+    spawn_blocking(move || {
+        let animation = load_frames(frames, width, height)?;
 
-        timestamp_ms += frame.delay;
-    }
+        handlers::encode_composed_webp(&animation)
+    })
+    .await
+    .map_err(|e| Error::ImageProcessingError(e.to_string()))?
+}
 
-    // 4. 结束并生成最终 WebP 二进制
-    let webp_data = encoder.finalize(timestamp_ms)
-        // 使用 ImageProcessingError
-        .map_err(|e| Error::ImageProcessingError(format!("Finalize failed: {}", e)))?;
+/// like encode_webp_anim, but also to apng or gif
+#[command]
+pub(crate) async fn encode_anim<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    frames: Vec<WebPFrame>,
+    width: u32,
+    height: u32,
+    format: handlers::AnimationFormat,
+    gif_options: Option<handlers::GifOptions>,
+) -> Result<Vec<u8>> {
+    spawn_blocking(move || {
+        let animation = load_frames(frames, width, height)?;
 
-    Ok(webp_data.to_vec())
+        handlers::encode_composed(&animation, format, &gif_options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| Error::ImageProcessingError(e.to_string()))?
 }
 
 #[command]
@@ -80,6 +118,11 @@ pub(crate) async fn get_server_url<R: Runtime>(
         "url".to_string(),
         Value::String(format!("http://localhost:{}", state.port)),
     );
+    // required by every request, as `x-wz-token` header or `token` query
+    map.insert(
+        "token".to_string(),
+        Value::String(state.access.get_token().to_string()),
+    );
     map.insert("is_initialized".to_string(), Value::Bool(!root.is_null()));
     map.insert(
         "is_load_items".to_string(),
@@ -99,12 +142,13 @@ pub(crate) async fn get_server_url<R: Runtime>(
 
 #[command]
 pub(crate) async fn init<R: Runtime>(
-    _app: AppHandle<R>,
+    app: AppHandle<R>,
     _window: Window<R>,
     state: State<'_, AppStore>,
     path: String,
     version: Option<String>,
-) -> Result<i32> {
+    pack_families: Option<Vec<String>>,
+) -> Result<LoadReport> {
     let current_root_path = state
         .node
         .read()
@@ -114,23 +158,72 @@ pub(crate) async fn init<R: Runtime>(
 
     if let Some(current_root_path) = current_root_path {
         if current_root_path == path {
-            return Ok(0);
+            return Ok(state.load_report.read().unwrap().clone().unwrap_or_default());
         }
     }
 
-    let version = version.map(|s| match s.as_str() {
-        "GMS" => WzMapleVersion::GMS,
-        "EMS" => WzMapleVersion::EMS,
-        "BMS" => WzMapleVersion::BMS,
-        _ => WzMapleVersion::UNKNOWN,
+    // a folder is a dump of .img or .xml files instead of the client
+    let is_dump = Path::new(&path).is_dir();
+
+    // version can be a region preset (GMS, KMS, MSEA...), AUTO or a custom iv in hex
+    let wz_key = match version {
+        Some(version) => {
+            let selection = utils::parse_key_selection(&version)?;
+            if is_dump {
+                utils::resolve_dump_key(selection)
+            } else {
+                utils::resolve_wz_key(selection, &path).await?
+            }
+        }
+        None => utils::ResolvedWzKey::default(),
+    };
+
+    // fallback to the pack families in setting, like { "setting": { "packFamilies": ["Skill"] } }
+    let pack_families = pack_families.or_else(|| {
+        app.get_store("setting.bin")
+            .and_then(|s| s.get("setting"))
+            .and_then(|v| v.get("packFamilies").cloned())
+            .and_then(|v| serde_json::from_value::<Vec<String>>(v).ok())
     });
 
-    let base_node = utils::resolve_base(&path, version)
-        .await
-        .map_err(|_| crate::Error::InitWzFailed)?;
+    // fallback to the memory budget in setting, like { "setting": { "memoryBudgetMb": 2048 } }
+    let memory_budget = app
+        .get_store("setting.bin")
+        .and_then(|s| s.get("setting"))
+        .and_then(|v| v.get("memoryBudgetMb").and_then(|v| v.as_u64()));
+    if let Some(megabytes) = memory_budget {
+        state.images.set_budget(megabytes * MEGABYTE);
+    }
+
+    // the sources of the current root are kept until the new one loaded
+    let pack_sources = PackSources::default();
+
+    let options = utils::LoadOptions {
+        pack_families,
+        pack_sources: pack_sources.clone(),
+        keys: wz_key.keys,
+    };
+
+    let reporter = utils::LoadReporter::new(
+        &path,
+        Some(Box::new(move |progress| {
+            let _ = app.emit(INIT_PROGRESS_EVENT, progress);
+        })),
+    );
+
+    reporter.set_key(wz_key.label);
+
+    let base_node = if is_dump {
+        utils::resolve_dump(&path, wz_key.iv, reporter.clone()).await?
+    } else {
+        utils::resolve_base_with_report(&path, wz_key.version, &options, reporter.clone()).await?
+    };
 
     state.replace_root(&base_node);
 
+    let pack_sources = std::mem::take(&mut *pack_sources.write().unwrap());
+    *state.pack_sources.write().unwrap() = pack_sources;
+
     let version = state
         .node
         .read()
@@ -139,7 +232,11 @@ pub(crate) async fn init<R: Runtime>(
         .map(|f| f.wz_file_meta.patch_version)
         .unwrap_or(0);
 
-    Ok(version)
+    let report = reporter.finish(version);
+
+    *state.load_report.write().unwrap() = Some(report.clone());
+
+    Ok(report)
 }
 
 #[command]
@@ -149,12 +246,17 @@ pub(crate) async fn parse_node<R: Runtime>(
     state: State<'_, AppStore>,
     path: String,
 ) -> Result<()> {
-    let node_read = state.node.read().unwrap();
+    {
+        let node_read = state.node.read().unwrap();
+
+        let _ = node_read
+            .at_path(&path)
+            .map(|n| node_util::parse_node(&n))
+            .ok_or_else(|| Error::node_not_found(&node_read, &path))?;
+    }
 
-    let _ = node_read
-        .at_path(&path)
-        .map(|n| node_util::parse_node(&n))
-        .ok_or(Error::NodeNotFound)?;
+    state.images.touch(&path);
+    state.images.enforce();
 
     Ok(())
 }
@@ -170,7 +272,21 @@ pub(crate) async fn unparse_node<R: Runtime>(
 
     node.at_path(&path)
         .map(|n| n.write().unwrap().unparse())
-        .ok_or(Error::NodeNotFound)?;
+        .ok_or_else(|| Error::node_not_found(&node, &path))?;
+
+    Ok(())
+}
+
+/// 0 or None to disable the budget
+#[command]
+pub(crate) async fn set_memory_budget<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+    megabytes: Option<u64>,
+) -> Result<()> {
+    state.images.set_budget(megabytes.unwrap_or(0) * MEGABYTE);
+    state.images.enforce();
 
     Ok(())
 }
@@ -185,22 +301,13 @@ pub(crate) async fn get_node_info<R: Runtime>(
     let node = if path.is_empty() {
         state.node.clone()
     } else {
-        state
-            .node
-            .read()
-            .unwrap()
-            .at_path(&path)
-            .ok_or(Error::NodeNotFound)?
+        let node = state.node.read().unwrap().at_path(&path);
+        node.ok_or_else(|| Error::node_not_found(&state.node.read().unwrap(), &path))?
     };
 
     let node_read = node.read().unwrap();
 
-    // 修复 to_string 引用问题，直接用 serde_json::to_string
-    Ok(models::NodeInfo {
-        name: node_read.name.to_string(),
-        _type: serde_json::to_string(&node_read.object_type)?,
-        has_child: !node_read.children.is_empty(),
-    })
+    handlers::resolve_node_info(&node_read, state.get_pack_source(&path))
 }
 
 #[command]
@@ -213,40 +320,373 @@ pub(crate) async fn get_childs_info<R: Runtime>(
     let node = if path.is_empty() {
         state.node.clone()
     } else {
-        state
-            .node
-            .read()
-            .unwrap()
-            .at_path(&path)
-            .ok_or(Error::NodeNotFound)?
+        let node = state.node.read().unwrap().at_path(&path);
+        node.ok_or_else(|| Error::node_not_found(&state.node.read().unwrap(), &path))?
     };
 
     let node_read = node.read().unwrap();
 
-    Ok(node_read
-        .children
-        .values()
-        .map(|node| {
-            let node_read = node.read().unwrap();
-            models::NodeInfo {
-                name: node_read.name.to_string(),
-                _type: serde_json::to_string(&node_read.object_type).unwrap(),
-                has_child: !node_read.children.is_empty(),
-            }
-        })
-        .collect())
+    let page = handlers::resolve_children_info(&node_read, &path, 0, None, |child_path| {
+        state.get_pack_source(child_path)
+    })?;
+
+    Ok(page.items)
 }
 
+/// the body is a frame container, see handlers::webp for the layout
 #[command]
-pub(crate) fn encode_webp(request: ipc::Request) -> ipc::Response {
-    let ipc::InvokeBody::Raw(webp_data) = request.body() else {
-        return ipc::Response::new(vec![]);
+pub(crate) async fn encode_webp(request: ipc::Request<'_>) -> Result<ipc::Response> {
+    let ipc::InvokeBody::Raw(data) = request.body() else {
+        return Err(Error::InvalidFrameContainer(
+            "expected a raw binary body".to_string(),
+        ));
     };
-    // 依然引用 handlers::webp (旧代码)
-    let data = crate::handlers::webp::encode_wep_animation(&webp_data);
-    if let Ok(data) = data {
-        ipc::Response::new(data.to_vec())
-    } else {
-        ipc::Response::new(vec![])
+    let data = data.clone();
+
+    let webp = spawn_blocking(move || handlers::webp::encode_webp_container(&data))
+        .await
+        .map_err(|e| Error::ImageProcessingError(e.to_string()))??;
+
+    Ok(ipc::Response::new(webp))
+}
+
+#[command]
+pub(crate) async fn get_stats<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+) -> Result<stats::StatsReport> {
+    let root = state.node.clone();
+    let string_dict = state.string.clone();
+    let images = state.images.clone();
+    let image_cache = state.image_cache.clone();
+    let runtime = state.stats.clone();
+
+    spawn_blocking(move || {
+        stats::resolve_stats(&root, &string_dict, &images, &image_cache, &runtime)
+    })
+    .await
+    .map_err(|e| Error::Io(std::io::Error::other(e.to_string())))
+}
+
+#[command]
+pub(crate) async fn get_link_references<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+    path: String,
+) -> Result<LinkReferences> {
+    Ok(state.link_index.get_references(&path))
+}
+
+#[command]
+pub(crate) async fn find_duplicate_sprites<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+    path: String,
+    threshold: Option<u32>,
+) -> Result<handlers::DuplicateReport> {
+    let root = state.node.clone();
+    let mut options = handlers::DuplicateOptions::default();
+    if let Some(threshold) = threshold {
+        options.threshold = threshold;
+    }
+
+    let images = state.images.clone();
+
+    spawn_blocking(move || {
+        let node = handlers::path::get_node_parsed(&root, &path)?;
+        let report = handlers::resolve_duplicate_sprites(&node, &path, Some(&root), &options);
+
+        images.track_parsed(&node);
+        images.enforce();
+
+        report
+    })
+    .await
+    .map_err(|e| Error::ExportError(e.to_string()))?
+}
+
+fn get_mount_skill_overrides_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, e.to_string())))?;
+    Ok(dir.join(handlers::MOUNT_SKILL_OVERRIDES_FILE))
+}
+
+/// the mounts the skill data and the overrides can't map
+#[command]
+pub(crate) async fn get_mount_skill_report<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+) -> Result<handlers::MountSkillReport> {
+    if state.is_empty() {
+        return Err(Error::NotInitialized);
+    }
+
+    let root = state.node.clone();
+    let mount_skills = state.mount_skills.clone();
+    let overrides = state.mount_overrides.read().unwrap().clone();
+    let images = state.images.clone();
+
+    spawn_blocking(move || {
+        let mount_skill_map =
+            handlers::resolve_mount_skill_map(&root, &mount_skills, &overrides, &images);
+        handlers::resolve_mount_skill_report(&root, &mount_skill_map)
+    })
+    .await
+    .map_err(|e| Error::ExportError(e.to_string()))?
+}
+
+/// read the overrides file again after the user edited it, returns the count of overrides
+#[command]
+pub(crate) async fn reload_mount_skill_overrides<R: Runtime>(
+    app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+) -> Result<usize> {
+    let path = get_mount_skill_overrides_path(&app)?;
+    let overrides = handlers::load_mount_skill_overrides(&path)?;
+    let count = overrides.len();
+
+    *state.mount_overrides.write().unwrap() = overrides;
+
+    Ok(count)
+}
+
+#[command]
+pub(crate) async fn export_skill_bundle<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+    skill_id: String,
+    output: String,
+    as_folder: Option<bool>,
+) -> Result<String> {
+    let root = state.node.clone();
+    let images = state.images.clone();
+
+    spawn_blocking(move || {
+        let bundle = handlers::resolve_skill_bundle(&root, &skill_id);
+
+        handlers::track_skill_bundle_images(&root, &skill_id, &images);
+        images.enforce();
+
+        let bundle = bundle?;
+        let target =
+            handlers::save_skill_bundle(&bundle, Path::new(&output), as_folder.unwrap_or(false))?;
+
+        Ok(target.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| Error::ExportError(e.to_string()))?
+}
+
+#[command]
+pub(crate) async fn get_skill_timeline<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+    skill_id: String,
+    hit_delay: Option<i32>,
+    target_offset: Option<(i32, i32)>,
+) -> Result<handlers::SkillTimeline> {
+    let root = state.node.clone();
+    let images = state.images.clone();
+    let options = handlers::TimelineOptions {
+        hit_delay,
+        target_offset: target_offset.unwrap_or((0, 0)),
+    };
+
+    spawn_blocking(move || {
+        let timeline = handlers::resolve_skill_timeline(&root, &skill_id, &options);
+
+        handlers::track_skill_image(&root, &skill_id, &images);
+        images.enforce();
+
+        timeline
+    })
+    .await
+    .map_err(|e| Error::ExportError(e.to_string()))?
+}
+
+/// compose every effect layer of the skill into one animation
+#[command]
+pub(crate) async fn export_skill_timeline<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+    skill_id: String,
+    output: AnimationOutput,
+    hit_delay: Option<i32>,
+    target_offset: Option<(i32, i32)>,
+) -> Result<String> {
+    let root = state.node.clone();
+    let images = state.images.clone();
+    let options = handlers::TimelineOptions {
+        hit_delay,
+        target_offset: target_offset.unwrap_or((0, 0)),
+    };
+
+    spawn_blocking(move || {
+        let timeline = handlers::resolve_skill_timeline(&root, &skill_id, &options);
+
+        handlers::track_skill_image(&root, &skill_id, &images);
+        images.enforce();
+
+        let animation = handlers::render_skill_timeline(&root, &timeline?)?;
+        output.write(&animation)?;
+
+        Ok(output.path)
+    })
+    .await
+    .map_err(|e| Error::ExportError(e.to_string()))?
+}
+
+/// render the animation as the client plays it and save as webp, apng or gif
+#[command]
+pub(crate) async fn export_animation<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+    path: String,
+    output: AnimationOutput,
+    fps: Option<u32>,
+) -> Result<String> {
+    let root = state.node.clone();
+    let images = state.images.clone();
+    let options = handlers::RenderOptions {
+        fps: fps.unwrap_or(handlers::DEFAULT_SAMPLE_FPS),
+    };
+
+    spawn_blocking(move || {
+        let node = handlers::path::get_node_parsed(&root, &path)?;
+        let animation = handlers::render_animation(&node, Some(&root), &options);
+
+        images.track_parsed(&node);
+        images.enforce();
+
+        output.write(&animation?)?;
+
+        Ok(output.path)
+    })
+    .await
+    .map_err(|e| Error::ExportError(e.to_string()))?
+}
+
+/// write the animation for godot or unity into the `output` folder
+#[command]
+pub(crate) async fn export_engine_animation<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+    path: String,
+    target: handlers::EngineTarget,
+    output: String,
+) -> Result<String> {
+    let root = state.node.clone();
+    let images = state.images.clone();
+
+    spawn_blocking(move || {
+        let node = handlers::path::get_node_parsed(&root, &path)?;
+        let export = handlers::resolve_engine_export(&node, Some(&root), target);
+
+        images.track_parsed(&node);
+        images.enforce();
+
+        let export = export?;
+        let descriptor = handlers::save_engine_export(&export, Path::new(&output))?;
+
+        Ok(descriptor.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| Error::ExportError(e.to_string()))?
+}
+
+#[command]
+pub(crate) async fn export_node_xml<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+    path: String,
+    output: String,
+    inline: Option<bool>,
+) -> Result<String> {
+    let root = state.node.clone();
+    let images = state.images.clone();
+
+    spawn_blocking(move || {
+        let node = handlers::path::get_node_parsed(&root, &path)?;
+        let export = handlers::resolve_xml_export(&node, Some(&root), inline.unwrap_or(false));
+
+        // the node may be a folder, every image under it is parsed
+        images.track_parsed(&node);
+        images.enforce();
+
+        let export = export?;
+        let target = handlers::save_xml_export(&export, Path::new(&output))?;
+
+        Ok(target.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| Error::ExportError(e.to_string()))?
+}
+
+#[command]
+pub(crate) async fn start_export_job<R: Runtime>(
+    app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+    target: ExportTarget,
+    output: String,
+    as_folder: Option<bool>,
+) -> Result<u32> {
+    if state.is_empty() {
+        return Err(Error::NotInitialized);
     }
-}
\ No newline at end of file
+
+    let source = JobSource {
+        root: state.node.clone(),
+        images: state.images.clone(),
+        mount_skills: state.mount_skills.clone(),
+        mount_overrides: state.mount_overrides.read().unwrap().clone(),
+    };
+
+    let as_folder = as_folder.unwrap_or(false);
+    Ok(state
+        .jobs
+        .start(app, target, output.into(), as_folder, source))
+}
+
+#[command]
+pub(crate) async fn list_export_jobs<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+) -> Result<Vec<ExportProgress>> {
+    Ok(state.jobs.list())
+}
+
+#[command]
+pub(crate) async fn cancel_export_job<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+    job_id: u32,
+) -> Result<()> {
+    state.jobs.cancel(job_id)
+}
+
+#[command]
+pub(crate) async fn clear_export_jobs<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, AppStore>,
+) -> Result<()> {
+    state.jobs.clear_finished();
+
+    Ok(())
+}
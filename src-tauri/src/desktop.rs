@@ -0,0 +1,171 @@
+use std::sync::{Arc, RwLock};
+use tauri::{async_runtime, webview::PageLoadEvent, AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use wz_reader::WzNode;
+
+use crate::cache::{ResponseCache, DEFAULT_IMAGE_CACHE_BYTES};
+use crate::jobs::JobRegistry;
+use crate::link_index::LinkIndex;
+use crate::memory::ImageTracker;
+use crate::server::{self, access::ServerAccess, ServerOptions, ServerState};
+use crate::stats::SharedRuntimeStats;
+use crate::{
+    commands, handlers, AppStore, MountSkillCache, MountSkillOverrides, PackSources, StringDict,
+};
+
+/// the tauri app with the local server
+pub fn run() {
+    let port = if portpicker::is_free_tcp(server::DEFAULT_PORT) {
+        server::DEFAULT_PORT
+    } else {
+        // 如果 12258 被占用，则选择一个随机端口
+        portpicker::pick_unused_port().expect("no available port for wz server")
+    };
+
+    let string_dict = StringDict::default();
+
+    let root_node = WzNode::empty().into_lock();
+
+    let images = ImageTracker::new(Arc::clone(&root_node));
+
+    let runtime_stats = SharedRuntimeStats::default();
+
+    let image_cache = ResponseCache::new(DEFAULT_IMAGE_CACHE_BYTES);
+
+    let pack_sources = PackSources::default();
+
+    let link_index = LinkIndex::new();
+    link_index.spawn_indexer(Arc::clone(&images));
+
+    let access = ServerAccess::new();
+
+    let mount_overrides = MountSkillOverrides::default();
+    let mount_skills = MountSkillCache::default();
+
+    let default_lang = Arc::new(sys_locale::get_locale().unwrap_or_else(|| String::from("en-US")));
+
+    let server_state = ServerState {
+        node: Arc::clone(&root_node),
+        string_dict: Arc::clone(&string_dict),
+        images: Arc::clone(&images),
+        stats: Arc::clone(&runtime_stats),
+        image_cache: Arc::clone(&image_cache),
+        pack_sources: Arc::clone(&pack_sources),
+        link_index: Arc::clone(&link_index),
+        access: Arc::clone(&access),
+        mount_overrides: Arc::clone(&mount_overrides),
+        mount_skills: Arc::clone(&mount_skills),
+    };
+
+    async_runtime::spawn(server::app(server_state, ServerOptions::local(port)));
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_store::Builder::new().build())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_single_instance::init(|app, _, _| {
+            let _ = show_window(app);
+        }))
+        .plugin(
+            tauri_plugin_window_state::Builder::new()
+                .with_filename("window-state.bin")
+                .build(),
+        )
+        .manage(AppStore {
+            node: root_node,
+            string: string_dict,
+            port,
+            jobs: JobRegistry::default(),
+            load_report: RwLock::new(None),
+            pack_sources,
+            images,
+            stats: runtime_stats,
+            image_cache,
+            link_index,
+            access: Arc::clone(&access),
+            mount_overrides: Arc::clone(&mount_overrides),
+            mount_skills,
+        })
+        .invoke_handler(tauri::generate_handler![
+            commands::get_server_url,
+            commands::init,
+            commands::parse_node,
+            commands::unparse_node,
+            commands::set_memory_budget,
+            commands::get_node_info,
+            commands::get_childs_info,
+            commands::get_stats,
+            commands::get_link_references,
+            commands::find_duplicate_sprites,
+            commands::get_mount_skill_report,
+            commands::reload_mount_skill_overrides,
+            commands::encode_webp,
+            commands::encode_webp_anim, // <--- 新增的命令
+            commands::encode_anim,
+            commands::export_skill_bundle,
+            commands::get_skill_timeline,
+            commands::export_skill_timeline,
+            commands::export_animation,
+            commands::export_engine_animation,
+            commands::export_node_xml,
+            commands::start_export_job,
+            commands::list_export_jobs,
+            commands::cancel_export_job,
+            commands::clear_export_jobs,
+        ])
+        .setup(move |app| {
+            // ensure the store file is created
+            let setting = app.store("setting.bin");
+
+            // the extra origins can call the server, like { "setting": { "allowedOrigins": [...] } }
+            let allowed_origins = setting
+                .ok()
+                .and_then(|s| s.get("setting"))
+                .and_then(|v| v.get("allowedOrigins").cloned())
+                .and_then(|v| serde_json::from_value::<Vec<String>>(v).ok());
+            if let Some(origins) = allowed_origins {
+                access.set_allowed_origins(origins);
+            }
+
+            // a broken overrides file shouldn't stop the app, the mapping from the data still works
+            let overrides_path = app
+                .path()
+                .app_data_dir()?
+                .join(handlers::MOUNT_SKILL_OVERRIDES_FILE);
+            match handlers::load_mount_skill_overrides(&overrides_path) {
+                Ok(overrides) => *mount_overrides.write().unwrap() = overrides,
+                Err(e) => eprintln!("failed to load {}: {}", overrides_path.display(), e),
+            }
+            Ok(())
+        })
+        .on_page_load(move |webview, payload| {
+            if payload.event() == PageLoadEvent::Started {
+                let setting = webview.app_handle().get_store("setting.bin");
+                let setting_lang = setting
+                    .map(|s| {
+                        s.get("setting")
+                            .and_then(|v| v.get("lang").and_then(|v| v.as_str().map(String::from)))
+                    })
+                    .flatten()
+                    .unwrap_or(String::clone(&default_lang));
+
+                let script = format!("window.__LANG__ = '{}'", setting_lang);
+                let _ = webview.eval(&script);
+            }
+        })
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
+
+fn show_window(app: &AppHandle) {
+    let windows = app.webview_windows();
+
+    windows
+        .values()
+        .next()
+        .expect("Sorry, no window found")
+        .set_focus()
+        .expect("Can't Bring Window to Focus");
+}
@@ -1,5 +1,6 @@
 use serde::{ser::Serializer, Serialize};
-use wz_reader::{node, property};
+use serde_json::{json, Value};
+use wz_reader::{node, property, WzNode};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -12,32 +13,60 @@ pub enum Error {
     #[error("node error: {0}")]
     NodeError(#[from] node::Error), // 修复大量 E0277, E0631 node 错误
 
-    #[error("json parse error")]
+    #[error("json parse error: {0}")]
     JsonParseError(#[from] serde_json::Error),
 
     // === WZ 解析相关错误 (必须保留) ===
-    #[error("image parse error")]
+    #[error("image parse error: {0}")]
     ImageParseError(#[from] property::png::WzPngParseError), // 修复 handlers/png.rs 错误
 
-    #[error("string parse error")]
+    #[error("string parse error: {0}")]
     StringParseError(#[from] property::string::WzStringParseError), // 修复 handlers/smap.rs 等错误
 
-    #[error("sound parse error")]
+    #[error("sound parse error: {0}")]
     SoundParseError(#[from] property::sound::WzSoundError), // 新增: 修复 sound 相关错误
 
+    #[error("xml error: {0}")]
+    XmlError(#[from] quick_xml::Error),
+
+    #[error("zip error: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+
     // === 业务逻辑错误 ===
     #[error("init wz failed")]
     InitWzFailed,
 
+    #[error("invalid wz key: {0}")]
+    InvalidWzKey(String),
+
     #[error("root wz not yet initialized, please use init command first")]
     NotInitialized,
 
-    #[error("node not found")]
-    NodeNotFound,
+    #[error("missing or invalid access token")]
+    Unauthorized,
+
+    #[error("node not found: {path}")]
+    NodeNotFound {
+        path: String,
+        /// the deepest part of the path which did resolve
+        resolved: String,
+    },
 
     #[error("node type mismatch, can only use on {0}")]
     NodeTypeMismatch(&'static str),
 
+    #[error("invalid search pattern: {0}")]
+    InvalidSearchPattern(String),
+
+    #[error("invalid skill id: {0}, expected digits only")]
+    InvalidSkillId(String),
+
+    #[error("invalid frame container: {0}")]
+    InvalidFrameContainer(String),
+
+    #[error("job not found")]
+    JobNotFound,
+
     #[error("image sending error")]
     ImageSendError,
 
@@ -48,12 +77,103 @@ pub enum Error {
     #[error("audio processing error: {0}")]
     AudioProcessingError(String), // 新增: 音频转换错误
 
+    #[error("export error: {0}")]
+    ExportError(String),
+
     // === 移动端错误 ===
-    #[cfg(mobile)]
+    #[cfg(all(mobile, feature = "desktop"))]
     #[error(transparent)]
     PluginInvoke(#[from] tauri::plugin::mobile::PluginInvokeError),
 }
 
+fn get_wz_reader_details(error: &impl std::fmt::Debug) -> Value {
+    json!({ "source": "wz_reader", "error": format!("{:?}", error) })
+}
+
+impl Error {
+    /// walk the path from the node to find how deep it resolves, the node itself must not be
+    /// locked by the caller except through the given reference
+    pub fn node_not_found(node: &WzNode, path: &str) -> Error {
+        let segments = path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut depth = 0;
+        let mut current = segments.first().and_then(|segment| node.at(segment));
+
+        while let Some(next) = current {
+            depth += 1;
+            current = segments
+                .get(depth)
+                .and_then(|segment| next.read().unwrap().at(segment));
+        }
+
+        Error::NodeNotFound {
+            path: path.to_string(),
+            resolved: segments[..depth].join("/"),
+        }
+    }
+
+    /// stable code for the clients, never change the existing ones
+    pub fn get_code(&self) -> &'static str {
+        match self {
+            Error::Io(_) => "IO_ERROR",
+            Error::NodeError(node::Error::NodeNotFound) => "NODE_NOT_FOUND",
+            Error::NodeError(_) => "WZ_NODE_ERROR",
+            Error::JsonParseError(_) => "JSON_ERROR",
+            Error::ImageParseError(_) => "CANVAS_DECODE_FAILED",
+            Error::StringParseError(_) => "STRING_DECODE_FAILED",
+            Error::SoundParseError(_) => "SOUND_DECODE_FAILED",
+            Error::XmlError(_) => "XML_ERROR",
+            Error::ZipError(_) => "ZIP_ERROR",
+            Error::InitWzFailed => "INIT_WZ_FAILED",
+            Error::InvalidWzKey(_) => "INVALID_WZ_KEY",
+            Error::NotInitialized => "NOT_INITIALIZED",
+            Error::Unauthorized => "UNAUTHORIZED",
+            Error::NodeNotFound { .. } => "NODE_NOT_FOUND",
+            Error::NodeTypeMismatch(_) => "NODE_TYPE_MISMATCH",
+            Error::InvalidSearchPattern(_) => "INVALID_SEARCH_PATTERN",
+            Error::InvalidSkillId(_) => "INVALID_SKILL_ID",
+            Error::InvalidFrameContainer(_) => "INVALID_FRAME_CONTAINER",
+            Error::JobNotFound => "JOB_NOT_FOUND",
+            Error::ImageSendError => "IMAGE_ENCODE_FAILED",
+            Error::ImageProcessingError(_) => "IMAGE_PROCESSING_FAILED",
+            Error::AudioProcessingError(_) => "AUDIO_PROCESSING_FAILED",
+            Error::ExportError(_) => "EXPORT_FAILED",
+            #[cfg(all(mobile, feature = "desktop"))]
+            Error::PluginInvoke(_) => "PLUGIN_INVOKE_FAILED",
+        }
+    }
+
+    /// the node path the error is about
+    pub fn get_path(&self) -> Option<&str> {
+        match self {
+            Error::NodeNotFound { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// the extra fields to tell the failures apart, like the underlying wz_reader error
+    pub fn get_details(&self) -> Option<Value> {
+        match self {
+            Error::NodeNotFound { path, resolved } => {
+                let depth = resolved.split('/').filter(|s| !s.is_empty()).count();
+                let missing = path.split('/').filter(|s| !s.is_empty()).nth(depth);
+                Some(json!({ "resolved": resolved, "missing": missing }))
+            }
+            Error::NodeTypeMismatch(expected) => Some(json!({ "expected": expected })),
+            Error::InvalidWzKey(key) => Some(json!({ "key": key })),
+            Error::NodeError(e) => Some(get_wz_reader_details(e)),
+            Error::ImageParseError(e) => Some(get_wz_reader_details(e)),
+            Error::StringParseError(e) => Some(get_wz_reader_details(e)),
+            Error::SoundParseError(e) => Some(get_wz_reader_details(e)),
+            Error::Io(e) => Some(json!({ "kind": format!("{:?}", e.kind()) })),
+            _ => None,
+        }
+    }
+}
+
 impl Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
@@ -0,0 +1,246 @@
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame as GifFrame, RgbaImage};
+use serde::Deserialize;
+use webp_animation::{Encoder, EncoderOptions};
+
+use crate::{Error, Result};
+
+/// the frames share one canvas, every encoder takes this as input
+pub struct ComposedAnimation {
+    pub width: u32,
+    pub height: u32,
+    /// where the origin is on the canvas, the character origin for a skill
+    pub origin: (i32, i32),
+    /// loop like the client, otherwise play once
+    pub repeat: bool,
+    /// (image, delay)
+    pub frames: Vec<(RgbaImage, i32)>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnimationFormat {
+    #[default]
+    WebP,
+    Apng,
+    Gif,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GifOptions {
+    /// pixels with alpha below this are transparent, the rest are opaque
+    pub alpha_threshold: u8,
+    /// blend the semi transparent pixels over this color instead of dropping their alpha
+    pub matte: Option<[u8; 3]>,
+    /// 1 ~ 30, lower is slower but gives a better palette
+    pub speed: i32,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        GifOptions {
+            alpha_threshold: 128,
+            matte: None,
+            speed: 10,
+        }
+    }
+}
+
+impl AnimationFormat {
+    pub fn get_extension(&self) -> &'static str {
+        match self {
+            AnimationFormat::WebP => "webp",
+            AnimationFormat::Apng => "png",
+            AnimationFormat::Gif => "gif",
+        }
+    }
+
+    pub fn get_mime_type(&self) -> &'static str {
+        match self {
+            AnimationFormat::WebP => "image/webp",
+            AnimationFormat::Apng => "image/apng",
+            AnimationFormat::Gif => "image/gif",
+        }
+    }
+}
+
+impl ComposedAnimation {
+    /// frames of the same size, like the ones drawn by the frontend
+    pub fn from_frames(width: u32, height: u32, frames: Vec<(RgbaImage, i32)>) -> Result<Self> {
+        if let Some(index) = frames
+            .iter()
+            .position(|(image, _)| image.dimensions() != (width, height))
+        {
+            return Err(Error::ImageProcessingError(format!(
+                "frame {} is {}x{}, expected {}x{}",
+                index,
+                frames[index].0.width(),
+                frames[index].0.height(),
+                width,
+                height,
+            )));
+        }
+
+        Ok(ComposedAnimation {
+            width,
+            height,
+            origin: (0, 0),
+            repeat: true,
+            frames,
+        })
+    }
+
+    /// the start time of every frame and the end time, negative delays count as 0
+    fn get_timestamps(&self) -> Vec<i32> {
+        let mut time = 0;
+        let mut timestamps = vec![0];
+        for (_, delay) in self.frames.iter() {
+            time += (*delay).max(0);
+            timestamps.push(time);
+        }
+        timestamps
+    }
+}
+
+pub fn encode_composed(
+    animation: &ComposedAnimation,
+    format: AnimationFormat,
+    gif_options: &GifOptions,
+) -> Result<Vec<u8>> {
+    if animation.frames.is_empty() {
+        return Err(Error::ImageProcessingError(
+            "the animation has no frame".to_string(),
+        ));
+    }
+
+    match format {
+        AnimationFormat::WebP => encode_composed_webp(animation),
+        AnimationFormat::Apng => encode_composed_apng(animation),
+        AnimationFormat::Gif => encode_composed_gif(animation, gif_options),
+    }
+}
+
+/// encode the composed animation as webp, loop forever when the animation repeats
+pub fn encode_composed_webp(animation: &ComposedAnimation) -> Result<Vec<u8>> {
+    let mut options = EncoderOptions::default();
+    options.anim_params.loop_count = if animation.repeat { 0 } else { 1 };
+
+    let mut encoder = Encoder::new_with_options((animation.width, animation.height), options)
+        .map_err(|e| Error::ImageProcessingError(format!("Encoder init failed: {}", e)))?;
+
+    let timestamps = animation.get_timestamps();
+    for ((image, _), timestamp) in animation.frames.iter().zip(timestamps.iter()) {
+        encoder
+            .add_frame(image.as_raw(), *timestamp)
+            .map_err(|e| Error::ImageProcessingError(format!("Add frame failed: {}", e)))?;
+    }
+
+    let data = encoder
+        .finalize(*timestamps.last().unwrap_or(&0))
+        .map_err(|e| Error::ImageProcessingError(format!("Finalize failed: {}", e)))?;
+
+    Ok(data.to_vec())
+}
+
+/// the fcTL delay fraction, in ms unless it overflows u16
+fn get_apng_delay(delay: i32) -> (u16, u16) {
+    let delay = delay.max(0) as u32;
+    if delay <= u16::MAX as u32 {
+        (delay as u16, 1000)
+    } else {
+        ((delay / 10).min(u16::MAX as u32) as u16, 100)
+    }
+}
+
+/// lossless and keeps the full alpha, every frame covers the whole canvas
+pub fn encode_composed_apng(animation: &ComposedAnimation) -> Result<Vec<u8>> {
+    let map_err = |e: png::EncodingError| Error::ImageProcessingError(e.to_string());
+
+    let mut buf = Vec::new();
+    let mut encoder = png::Encoder::new(&mut buf, animation.width, animation.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(
+            animation.frames.len() as u32,
+            if animation.repeat { 0 } else { 1 },
+        )
+        .map_err(map_err)?;
+
+    let mut writer = encoder.write_header().map_err(map_err)?;
+    for (image, delay) in animation.frames.iter() {
+        let (numerator, denominator) = get_apng_delay(*delay);
+        writer
+            .set_frame_delay(numerator, denominator)
+            .map_err(map_err)?;
+        writer.set_blend_op(png::BlendOp::Source).map_err(map_err)?;
+        writer.write_image_data(image.as_raw()).map_err(map_err)?;
+    }
+    writer.finish().map_err(map_err)?;
+
+    Ok(buf)
+}
+
+/// gif has 1 bit alpha, drop the pixels below the threshold and flatten the rest
+fn flatten_alpha(image: &RgbaImage, options: &GifOptions) -> RgbaImage {
+    let mut image = image.clone();
+
+    for pixel in image.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        if a < options.alpha_threshold.max(1) {
+            pixel.0 = [0, 0, 0, 0];
+            continue;
+        }
+        pixel.0 = match options.matte {
+            Some(matte) => {
+                let blend = |color: u8, matte: u8| {
+                    ((color as u32 * a as u32 + matte as u32 * (255 - a as u32)) / 255) as u8
+                };
+                [
+                    blend(r, matte[0]),
+                    blend(g, matte[1]),
+                    blend(b, matte[2]),
+                    255,
+                ]
+            }
+            None => [r, g, b, 255],
+        };
+    }
+
+    image
+}
+
+/// gif counts in centiseconds, round the timestamps so the rounding error doesn't add up
+pub fn encode_composed_gif(animation: &ComposedAnimation, options: &GifOptions) -> Result<Vec<u8>> {
+    let map_err = |e: image::ImageError| Error::ImageProcessingError(e.to_string());
+
+    let mut buf = Vec::new();
+    {
+        let mut encoder = GifEncoder::new_with_speed(&mut buf, options.speed.clamp(1, 30));
+        // without the loop extension the gif plays once
+        if animation.repeat {
+            encoder.set_repeat(Repeat::Infinite).map_err(map_err)?;
+        }
+
+        let centiseconds = animation
+            .get_timestamps()
+            .iter()
+            .map(|time| (*time as f64 / 10.0).round() as u32)
+            .collect::<Vec<_>>();
+
+        let frames =
+            animation
+                .frames
+                .iter()
+                .zip(centiseconds.windows(2))
+                .map(|((image, _), window)| {
+                    let delay = Delay::from_numer_denom_ms((window[1] - window[0]) * 10, 1);
+                    GifFrame::from_parts(flatten_alpha(image, options), 0, 0, delay)
+                });
+
+        encoder.encode_frames(frames).map_err(map_err)?;
+    }
+
+    Ok(buf)
+}
@@ -0,0 +1,204 @@
+use image::DynamicImage;
+use rayon::prelude::*;
+use serde::Serialize;
+use wz_reader::{
+    property::{WzSubProperty, WzValue},
+    util::node_util,
+    WzNode, WzNodeArc, WzNodeCast, WzObjectType,
+};
+
+use super::png::resolve_png;
+use crate::utils::DUMP_CANVAS_KEY;
+use crate::{Error, Result};
+
+pub const DEFAULT_FRAME_DELAY: i32 = 100;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameMeta {
+    pub index: usize,
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    pub origin: (i32, i32),
+    pub delay: i32,
+    pub z: Option<i32>,
+}
+
+pub struct Frame {
+    pub meta: FrameMeta,
+    pub image: DynamicImage,
+}
+
+/// read a number from child node, some wz store number as string so also try to parse it
+pub fn get_int_at(node: &WzNode, key: &str) -> Option<i32> {
+    let child = node.at(key)?;
+    let child = child.read().unwrap();
+
+    match &child.object_type {
+        WzObjectType::Value(WzValue::Short(v)) => Some(*v as i32),
+        WzObjectType::Value(WzValue::Int(v)) => Some(*v),
+        WzObjectType::Value(WzValue::Long(v)) => Some(*v as i32),
+        _ => child
+            .try_as_string()
+            .and_then(|s| s.get_string().ok())
+            .and_then(|s| s.trim().parse().ok()),
+    }
+}
+
+pub fn get_vector_at(node: &WzNode, key: &str) -> Option<(i32, i32)> {
+    let child = node.at(key)?;
+    let child = child.read().unwrap();
+
+    child.try_as_vector2d().map(|v| (v.0, v.1))
+}
+
+/// a frame node is a png, a png link, a dump canvas or a uol point to a png
+pub fn is_frame_node(node: &WzNode) -> bool {
+    matches!(
+        node.object_type,
+        WzObjectType::Property(WzSubProperty::PNG(_))
+    ) || node.try_as_uol().is_some()
+        || node.at(DUMP_CANVAS_KEY).is_some()
+        || node.at("_inlink").is_some()
+        || node.at("_outlink").is_some()
+}
+
+/// walk the uol target from the uol's parent, like `../../0`, for the trees without images
+/// to look up from the root, like a xml dump
+fn resolve_relative_uol(uol_node: &WzNodeArc, target: &str) -> Option<WzNodeArc> {
+    let mut current = uol_node.read().unwrap().parent.upgrade()?;
+
+    for segment in target.split('/').filter(|s| !s.is_empty()) {
+        current = if segment == ".." {
+            current.read().unwrap().parent.upgrade()?
+        } else {
+            current.read().unwrap().at(segment)?
+        };
+    }
+
+    Some(current)
+}
+
+/// follow the uol until reach a non-uol node
+pub fn resolve_uol_node(node: &WzNodeArc, root: Option<&WzNodeArc>) -> Option<WzNodeArc> {
+    let mut current = node.clone();
+
+    // guard against uol loop
+    for _ in 0..8 {
+        let uol = {
+            let current_read = current.read().unwrap();
+            match current_read.try_as_uol() {
+                Some(uol) => {
+                    let target = uol.get_string().ok()?;
+                    let path =
+                        node_util::get_resolved_uol_path(&current_read.get_full_path(), &target);
+                    Some((target, path))
+                }
+                None => None,
+            }
+        };
+
+        let Some((target, uol_path)) = uol else {
+            return Some(current);
+        };
+
+        current = root
+            .and_then(|root| node_util::get_node_without_parse(root, &uol_path))
+            .or_else(|| resolve_relative_uol(&current, &target))?;
+    }
+
+    None
+}
+
+/// find every animation container under the node, the container is a node with a "0" frame child.
+///
+/// the returned name is the path relative to the given node, like `effect` or `hit/0`
+pub fn find_animation_nodes(node: &WzNodeArc) -> Vec<(String, WzNodeArc)> {
+    let mut result = Vec::new();
+
+    collect_animation_nodes(node, "", &mut result);
+
+    result
+}
+
+fn collect_animation_nodes(node: &WzNodeArc, prefix: &str, result: &mut Vec<(String, WzNodeArc)>) {
+    let node_read = node.read().unwrap();
+
+    let is_animation = node_read
+        .at("0")
+        .map_or(false, |first| is_frame_node(&first.read().unwrap()));
+
+    if is_animation && !prefix.is_empty() {
+        result.push((prefix.to_string(), node.clone()));
+        return;
+    }
+
+    let mut children = node_read
+        .children
+        .iter()
+        .map(|(name, child)| (name.to_string(), child.clone()))
+        .collect::<Vec<_>>();
+
+    children.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (name, child) in children {
+        let path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        collect_animation_nodes(&child, &path, result);
+    }
+}
+
+/// get the ordered frame nodes of a animation container, stop at the first missing index
+pub fn get_frame_nodes(anim_node: &WzNodeArc) -> Vec<WzNodeArc> {
+    let anim_read = anim_node.read().unwrap();
+
+    (0..)
+        .map_while(|i: usize| anim_read.at(&i.to_string()))
+        .filter(|node| is_frame_node(&node.read().unwrap()))
+        .collect()
+}
+
+pub fn resolve_frame(
+    index: usize,
+    frame_node: &WzNodeArc,
+    root: Option<&WzNodeArc>,
+) -> Result<Frame> {
+    // the frame itself exists, only its uol target is missing
+    let frame_node = resolve_uol_node(frame_node, root).ok_or_else(|| {
+        let path = frame_node.read().unwrap().get_full_path();
+        Error::NodeNotFound {
+            resolved: path.clone(),
+            path,
+        }
+    })?;
+    let image = resolve_png(&frame_node, root)?;
+    let frame_read = frame_node.read().unwrap();
+
+    let meta = FrameMeta {
+        index,
+        path: frame_read.get_full_path(),
+        width: image.width(),
+        height: image.height(),
+        origin: get_vector_at(&frame_read, "origin").unwrap_or((0, 0)),
+        delay: get_int_at(&frame_read, "delay").unwrap_or(DEFAULT_FRAME_DELAY),
+        z: get_int_at(&frame_read, "z"),
+    };
+
+    Ok(Frame { meta, image })
+}
+
+/// decode every frame of the animation container in parallel
+pub fn resolve_animation_frames(
+    anim_node: &WzNodeArc,
+    root: Option<&WzNodeArc>,
+) -> Result<Vec<Frame>> {
+    get_frame_nodes(anim_node)
+        .par_iter()
+        .enumerate()
+        .map(|(index, node)| resolve_frame(index, node, root))
+        .collect()
+}
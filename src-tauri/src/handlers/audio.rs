@@ -1,5 +1,6 @@
 use std::io::Cursor;
-use crate::{Error, Result};
+use crate::{utils, Error, Result};
+use wz_reader::{WzNode, WzNodeCast};
 
 // 引入 vorbis_rs 用于编码
 use vorbis_rs::{VorbisBitrateManagementStrategy, VorbisEncoderBuilder};
@@ -12,6 +13,15 @@ use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 
+/// get the sound buffer from a WzSound or a sound from dump
+pub fn resolve_sound_buffer(node: &WzNode) -> Result<Vec<u8>> {
+    if let Some(sound) = node.try_as_sound() {
+        return Ok(sound.get_buffer());
+    }
+
+    utils::resolve_dump_sound(node).unwrap_or(Err(Error::NodeTypeMismatch("WzSound")))
+}
+
 /// 将任意音频数据 (WAV/MP3) 转换为 Ogg Vorbis 格式
 pub fn convert_audio_to_ogg(input_data: &[u8]) -> Result<Vec<u8>> {
     // ============================
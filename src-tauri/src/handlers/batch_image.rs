@@ -0,0 +1,128 @@
+use std::io::Cursor;
+
+use image::ImageFormat;
+use rayon::prelude::*;
+use wz_reader::WzNodeArc;
+
+use super::animation::{get_frame_nodes, resolve_frame, Frame};
+use super::path::get_node_parsed;
+use crate::{Error, Result};
+
+// the container layout, every number is little endian
+// header: [magic:"WZBF", version:u8, reserved:[u8;3], frame_count:u32]
+// frame:  [path_len:u16, path:utf8, width:u32, height:u32, origin_x:i32, origin_y:i32,
+//          delay:i32, data_len:u32, data:webp]
+// a frame failed to resolve has zero width, height and data_len
+
+pub const BATCH_IMAGE_MAGIC: &[u8; 4] = b"WZBF";
+pub const BATCH_IMAGE_VERSION: u8 = 1;
+
+/// a frame in the batch, keep the requested path even it failed to resolve
+pub struct BatchFrame {
+    pub path: String,
+    pub frame: Option<Frame>,
+}
+
+fn resolve_batch_frame(root: &WzNodeArc, index: usize, path: &str) -> BatchFrame {
+    let frame = root
+        .read()
+        .unwrap()
+        .at_path_parsed(path)
+        .ok()
+        .and_then(|node| resolve_frame(index, &node, Some(root)).ok());
+
+    BatchFrame {
+        path: path.to_string(),
+        frame,
+    }
+}
+
+/// decode the nodes at paths in parallel
+pub fn resolve_batch_frames(root: &WzNodeArc, paths: &[String]) -> Vec<BatchFrame> {
+    paths
+        .par_iter()
+        .enumerate()
+        .map(|(index, path)| resolve_batch_frame(root, index, path))
+        .collect()
+}
+
+/// decode every frame of the animation container in parallel
+pub fn resolve_batch_animation(root: &WzNodeArc, path: &str) -> Result<Vec<BatchFrame>> {
+    let anim_node = get_node_parsed(root, path)?;
+
+    let frames = get_frame_nodes(&anim_node)
+        .par_iter()
+        .enumerate()
+        .map(|(index, node)| {
+            let path = format!("{}/{}", path.trim_end_matches('/'), index);
+            BatchFrame {
+                frame: resolve_frame(index, node, Some(root)).ok(),
+                path,
+            }
+        })
+        .collect();
+
+    Ok(frames)
+}
+
+fn encode_webp(frame: &Frame) -> Result<Vec<u8>> {
+    let mut buf = Cursor::new(Vec::new());
+    frame
+        .image
+        .write_to(&mut buf, ImageFormat::WebP)
+        .map_err(|e| Error::ImageProcessingError(e.to_string()))?;
+
+    Ok(buf.into_inner())
+}
+
+fn write_frame(buf: &mut Vec<u8>, path: &str, frame: Option<&Frame>, data: &[u8]) {
+    // the length is u16, a longer path is cut on a char boundary so it stays valid utf8
+    let mut path_len = path.len().min(u16::MAX as usize);
+    while !path.is_char_boundary(path_len) {
+        path_len -= 1;
+    }
+
+    buf.extend_from_slice(&(path_len as u16).to_le_bytes());
+    buf.extend_from_slice(&path.as_bytes()[..path_len]);
+
+    let (width, height, origin, delay) = frame.map_or((0, 0, (0, 0), 0), |frame| {
+        let meta = &frame.meta;
+        (meta.width, meta.height, meta.origin, meta.delay)
+    });
+
+    buf.extend_from_slice(&width.to_le_bytes());
+    buf.extend_from_slice(&height.to_le_bytes());
+    buf.extend_from_slice(&origin.0.to_le_bytes());
+    buf.extend_from_slice(&origin.1.to_le_bytes());
+    buf.extend_from_slice(&delay.to_le_bytes());
+    buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(data);
+}
+
+/// encode the frames into the batch container, the webp encoding also runs in parallel
+pub fn encode_batch_container(frames: &[BatchFrame]) -> Result<Vec<u8>> {
+    let encoded = frames
+        .par_iter()
+        .map(|batch_frame| match &batch_frame.frame {
+            Some(frame) => encode_webp(frame).map(Some),
+            None => Ok(None),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut buf = Vec::with_capacity(
+        encoded.iter().flatten().map(|data| data.len() + 64).sum::<usize>() + 12,
+    );
+
+    buf.extend_from_slice(BATCH_IMAGE_MAGIC);
+    buf.push(BATCH_IMAGE_VERSION);
+    buf.extend_from_slice(&[0; 3]);
+    buf.extend_from_slice(&(frames.len() as u32).to_le_bytes());
+
+    for (batch_frame, data) in frames.iter().zip(encoded.iter()) {
+        let frame = data.as_ref().and(batch_frame.frame.as_ref());
+        let data = data.as_deref().unwrap_or_default();
+        write_frame(&mut buf, &batch_frame.path, frame, data);
+    }
+
+    Ok(buf)
+}
@@ -13,7 +13,9 @@ use crate::{Error, Result};
 pub fn resolve_chair_string(root: &WzNodeArc) -> Result<Vec<(String, String, String)>> {
     let root_read = root.read().unwrap();
     let mut result = vec![];
-    let chair_folders_node = root_read.at_path(CHAIR_PATH).ok_or(Error::NodeNotFound)?;
+    let chair_folders_node = root_read
+        .at_path(CHAIR_PATH)
+        .ok_or_else(|| Error::node_not_found(&root_read, CHAIR_PATH))?;
 
     let string_node = {
         let string_node = root_read.at_path(CHAIR_STRING_PATH);
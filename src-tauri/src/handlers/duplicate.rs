@@ -0,0 +1,266 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
+use rayon::prelude::*;
+use serde::Serialize;
+use wz_reader::{util::node_util, WzNodeArc, WzNodeCast};
+
+use super::png::resolve_png;
+use crate::Result;
+
+/// the banding only guarantee to find the pairs within 3 bits
+pub const MAX_DHASH_DISTANCE: u32 = 3;
+const DEFAULT_MIN_PIXELS: u32 = 16;
+
+pub struct DuplicateOptions {
+    /// max hamming distance of dhash to be a near duplicate, 0 for exact duplicate only
+    pub threshold: u32,
+    /// skip the tiny placeholder images
+    pub min_pixels: u32,
+}
+
+impl Default for DuplicateOptions {
+    fn default() -> Self {
+        DuplicateOptions {
+            threshold: MAX_DHASH_DISTANCE,
+            min_pixels: DEFAULT_MIN_PIXELS,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateMember {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    /// hamming distance of dhash to the representative
+    pub distance: u32,
+    /// byte-identical to the representative
+    pub exact: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateCluster {
+    /// the first path in the cluster, use `/node/image/<representative>` as preview
+    pub representative: String,
+    pub members: Vec<DuplicateMember>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateReport {
+    pub scanned: usize,
+    pub failed: usize,
+    pub clusters: Vec<DuplicateCluster>,
+}
+
+struct SpriteHash {
+    path: String,
+    width: u32,
+    height: u32,
+    exact: u64,
+    dhash: u64,
+}
+
+/// png nodes under the node, links are skipped since they are not copies
+fn collect_png_nodes(
+    node: &WzNodeArc,
+    path: &str,
+    result: &mut Vec<(String, WzNodeArc)>,
+) -> Result<()> {
+    let is_image = node.read().unwrap().try_as_image().is_some();
+    if is_image {
+        node_util::parse_node(node)?;
+    }
+
+    let node_read = node.read().unwrap();
+
+    if node_read.try_as_png().is_some()
+        && node_read.at("_inlink").is_none()
+        && node_read.at("_outlink").is_none()
+    {
+        result.push((path.to_string(), node.clone()));
+    }
+
+    for (name, child) in node_read.children.iter() {
+        let child_path = if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", path, name)
+        };
+        collect_png_nodes(child, &child_path, result)?;
+    }
+
+    Ok(())
+}
+
+fn get_exact_hash(image: &DynamicImage) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.dimensions().hash(&mut hasher);
+    image.to_rgba8().as_raw().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// difference hash of 9x8 grayscale, the transparent pixels are treated as black
+pub fn get_dhash(image: &DynamicImage) -> u64 {
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_rgba8();
+
+    let luma = |x: u32, y: u32| {
+        let [r, g, b, a] = small.get_pixel(x, y).0;
+        let gray = (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+        gray * a as u32 / 255
+    };
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if luma(x, y) > luma(x + 1, y) {
+                hash |= 1;
+            }
+        }
+    }
+
+    hash
+}
+
+fn find_root(parents: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while parents[root] != root {
+        parents[root] = parents[parents[root]];
+        root = parents[root];
+    }
+    root
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find_root(parents, a), find_root(parents, b));
+    if a != b {
+        parents[a.max(b)] = a.min(b);
+    }
+}
+
+/// group by exact hash, then join the groups whose dhash are near by 16 bits banding
+fn cluster_sprites(sprites: &[SpriteHash], threshold: u32) -> Vec<Vec<usize>> {
+    let mut parents = (0..sprites.len()).collect::<Vec<_>>();
+
+    let mut exact_groups: HashMap<u64, usize> = HashMap::new();
+    for (index, sprite) in sprites.iter().enumerate() {
+        match exact_groups.get(&sprite.exact) {
+            Some(first) => union(&mut parents, *first, index),
+            None => {
+                exact_groups.insert(sprite.exact, index);
+            }
+        }
+    }
+
+    if threshold > 0 {
+        let representatives = exact_groups.values().copied().collect::<Vec<_>>();
+
+        for band in 0..4u32 {
+            let mut buckets: HashMap<u16, Vec<usize>> = HashMap::new();
+            for index in representatives.iter() {
+                let key = (sprites[*index].dhash >> (band * 16)) as u16;
+                buckets.entry(key).or_default().push(*index);
+            }
+
+            for bucket in buckets.values() {
+                for (i, a) in bucket.iter().enumerate() {
+                    for b in bucket[i + 1..].iter() {
+                        let distance = (sprites[*a].dhash ^ sprites[*b].dhash).count_ones();
+                        if distance <= threshold {
+                            union(&mut parents, *a, *b);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut clusters: HashMap<usize, Vec<usize>> = HashMap::new();
+    for index in 0..sprites.len() {
+        let root = find_root(&mut parents, index);
+        clusters.entry(root).or_default().push(index);
+    }
+
+    clusters
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .collect()
+}
+
+/// find the duplicate and near duplicate sprites under the node
+pub fn resolve_duplicate_sprites(
+    node: &WzNodeArc,
+    path: &str,
+    root: Option<&WzNodeArc>,
+    options: &DuplicateOptions,
+) -> Result<DuplicateReport> {
+    let mut png_nodes = Vec::new();
+    collect_png_nodes(node, path, &mut png_nodes)?;
+
+    let hashes = png_nodes
+        .par_iter()
+        .map(|(path, node)| {
+            let image = resolve_png(node, root).ok()?;
+            Some(SpriteHash {
+                path: path.clone(),
+                width: image.width(),
+                height: image.height(),
+                exact: get_exact_hash(&image),
+                dhash: get_dhash(&image),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let failed = hashes.iter().filter(|hash| hash.is_none()).count();
+    let sprites = hashes
+        .into_iter()
+        .flatten()
+        .filter(|sprite| sprite.width * sprite.height >= options.min_pixels)
+        .collect::<Vec<_>>();
+
+    let threshold = options.threshold.min(MAX_DHASH_DISTANCE);
+
+    let mut clusters = cluster_sprites(&sprites, threshold)
+        .into_iter()
+        .map(|mut members| {
+            members.sort_by(|a, b| sprites[*a].path.cmp(&sprites[*b].path));
+            let representative = &sprites[members[0]];
+
+            DuplicateCluster {
+                representative: representative.path.clone(),
+                members: members
+                    .iter()
+                    .map(|index| {
+                        let sprite = &sprites[*index];
+                        DuplicateMember {
+                            path: sprite.path.clone(),
+                            width: sprite.width,
+                            height: sprite.height,
+                            distance: (sprite.dhash ^ representative.dhash).count_ones(),
+                            exact: sprite.exact == representative.exact,
+                        }
+                    })
+                    .collect(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    clusters.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then_with(|| a.representative.cmp(&b.representative))
+    });
+
+    Ok(DuplicateReport {
+        scanned: png_nodes.len(),
+        failed,
+        clusters,
+    })
+}
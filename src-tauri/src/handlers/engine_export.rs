@@ -0,0 +1,357 @@
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use image::{imageops, DynamicImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+use wz_reader::WzNodeArc;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use super::animation::{resolve_animation_frames, Frame};
+use super::skill_export::encode_png;
+use crate::{Error, Result};
+
+/// clear pixels between the sprites, so the filtering doesn't bleed the neighbours
+const ATLAS_PADDING: u32 = 2;
+/// the godot speed, a frame with `duration = 1.0` lasts 100ms
+const GODOT_SPEED: f32 = 10.0;
+const UNITY_PIXELS_PER_UNIT: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineTarget {
+    /// a `SpriteFrames` resource with a sprite atlas
+    Godot,
+    /// a sprite atlas with a json of rects and pivots
+    Unity,
+}
+
+/// where a frame is placed in the atlas
+struct AtlasRect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+struct Atlas {
+    image: RgbaImage,
+    rects: Vec<AtlasRect>,
+}
+
+pub struct EngineExport {
+    pub name: String,
+    /// (file name, file content)
+    pub files: Vec<(String, Vec<u8>)>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnityPivot {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnitySprite {
+    name: String,
+    /// the rect in unity texture space, y starts from the bottom
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    /// normalized, (0, 0) is the bottom left
+    pivot: UnityPivot,
+    /// the origin in wz, from the top left
+    origin: (i32, i32),
+    /// in seconds
+    duration: f32,
+    delay: i32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnityManifest {
+    name: String,
+    path: String,
+    texture: String,
+    width: u32,
+    height: u32,
+    pixels_per_unit: u32,
+    sprites: Vec<UnitySprite>,
+}
+
+/// like `Skill/2121.img/skill/2121005/effect` -> `2121005_effect`
+pub fn get_engine_export_name(path: &str) -> String {
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.trim_end_matches(".img"))
+        .collect::<Vec<_>>();
+
+    let name = segments[segments.len().saturating_sub(2)..]
+        .join("_")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    if name.is_empty() {
+        "animation".to_string()
+    } else {
+        name
+    }
+}
+
+/// shelf packing, the frames are placed from the tallest so each shelf wastes less
+fn pack_atlas(images: &[DynamicImage]) -> Atlas {
+    let area = images
+        .iter()
+        .map(|image| {
+            (image.width() + ATLAS_PADDING) as u64 * (image.height() + ATLAS_PADDING) as u64
+        })
+        .sum::<u64>();
+    let max_width = images.iter().map(|image| image.width()).max().unwrap_or(0);
+    let atlas_width = ((area as f64).sqrt().ceil() as u32).max(max_width + ATLAS_PADDING);
+
+    let mut order = (0..images.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| std::cmp::Reverse(images[i].height()));
+
+    let mut rects = images
+        .iter()
+        .map(|_| AtlasRect {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+        })
+        .collect::<Vec<_>>();
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+
+    for index in order {
+        let (width, height) = (images[index].width(), images[index].height());
+        if x > 0 && x + width > atlas_width {
+            x = 0;
+            y += shelf_height + ATLAS_PADDING;
+            shelf_height = 0;
+        }
+        rects[index] = AtlasRect {
+            x,
+            y,
+            width,
+            height,
+        };
+        x += width + ATLAS_PADDING;
+        shelf_height = shelf_height.max(height);
+    }
+
+    let used_width = rects
+        .iter()
+        .map(|rect| rect.x + rect.width)
+        .max()
+        .unwrap_or(0);
+    let used_height = rects
+        .iter()
+        .map(|rect| rect.y + rect.height)
+        .max()
+        .unwrap_or(0);
+
+    let mut image = RgbaImage::new(used_width.max(1), used_height.max(1));
+    for (frame, rect) in images.iter().zip(rects.iter()) {
+        imageops::replace(&mut image, &frame.to_rgba8(), rect.x as i64, rect.y as i64);
+    }
+
+    Atlas { image, rects }
+}
+
+fn resolve_frames(anim_node: &WzNodeArc, root: Option<&WzNodeArc>) -> Result<(Vec<Frame>, Atlas)> {
+    let frames = resolve_animation_frames(anim_node, root)?;
+    if frames.is_empty() {
+        return Err(Error::NodeTypeMismatch("animation"));
+    }
+
+    let images = frames
+        .iter()
+        .map(|frame| frame.image.clone())
+        .collect::<Vec<_>>();
+    let atlas = pack_atlas(&images);
+
+    Ok((frames, atlas))
+}
+
+/// every frame is an AtlasTexture, its margin pads the frame to the union of all frames,
+/// so the origin stays at `origin_offset` of the shared canvas
+fn write_godot_sprite_frames(name: &str, texture: &str, frames: &[Frame], atlas: &Atlas) -> String {
+    let left = frames.iter().map(|f| -f.meta.origin.0).min().unwrap_or(0);
+    let top = frames.iter().map(|f| -f.meta.origin.1).min().unwrap_or(0);
+    let right = frames
+        .iter()
+        .map(|f| f.meta.width as i32 - f.meta.origin.0)
+        .max()
+        .unwrap_or(0);
+    let bottom = frames
+        .iter()
+        .map(|f| f.meta.height as i32 - f.meta.origin.1)
+        .max()
+        .unwrap_or(0);
+
+    let mut tres = format!(
+        "[gd_resource type=\"SpriteFrames\" load_steps={} format=3]\n\n",
+        frames.len() + 2
+    );
+    tres.push_str(&format!(
+        "[ext_resource type=\"Texture2D\" path=\"{}\" id=\"1_atlas\"]\n\n",
+        texture
+    ));
+
+    for (index, (frame, rect)) in frames.iter().zip(atlas.rects.iter()).enumerate() {
+        let margin_x = -frame.meta.origin.0 - left;
+        let margin_y = -frame.meta.origin.1 - top;
+        let margin_w = (right - left) - frame.meta.width as i32;
+        let margin_h = (bottom - top) - frame.meta.height as i32;
+
+        tres.push_str(&format!(
+            "[sub_resource type=\"AtlasTexture\" id=\"AtlasTexture_{index}\"]\n\
+             atlas = ExtResource(\"1_atlas\")\n\
+             region = Rect2({}, {}, {}, {})\n\
+             margin = Rect2({}, {}, {}, {})\n\n",
+            rect.x, rect.y, rect.width, rect.height, margin_x, margin_y, margin_w, margin_h,
+        ));
+    }
+
+    let frame_entries = frames
+        .iter()
+        .enumerate()
+        .map(|(index, frame)| {
+            format!(
+                "{{\n\"duration\": {:.2},\n\"texture\": SubResource(\"AtlasTexture_{}\")\n}}",
+                frame.meta.delay.max(0) as f32 * GODOT_SPEED / 1000.0,
+                index
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    tres.push_str("[resource]\n");
+    // set the AnimatedSprite2D to `centered = false` and `offset` to this
+    tres.push_str(&format!(
+        "metadata/origin_offset = Vector2({}, {})\n",
+        left, top
+    ));
+    tres.push_str(&format!(
+        "animations = [{{\n\"frames\": [{}],\n\"loop\": true,\n\"name\": &\"{}\",\n\"speed\": {:.1}\n}}]\n",
+        frame_entries, name, GODOT_SPEED
+    ));
+
+    tres
+}
+
+fn write_unity_manifest(
+    name: &str,
+    path: &str,
+    texture: &str,
+    frames: &[Frame],
+    atlas: &Atlas,
+) -> Result<Vec<u8>> {
+    let atlas_height = atlas.image.height();
+
+    let sprites = frames
+        .iter()
+        .zip(atlas.rects.iter())
+        .enumerate()
+        .map(|(index, (frame, rect))| {
+            let (origin_x, origin_y) = frame.meta.origin;
+            UnitySprite {
+                name: format!("{}_{}", name, index),
+                x: rect.x,
+                y: atlas_height - rect.y - rect.height,
+                width: rect.width,
+                height: rect.height,
+                pivot: UnityPivot {
+                    x: origin_x as f32 / rect.width.max(1) as f32,
+                    y: 1.0 - origin_y as f32 / rect.height.max(1) as f32,
+                },
+                origin: frame.meta.origin,
+                duration: frame.meta.delay.max(0) as f32 / 1000.0,
+                delay: frame.meta.delay,
+            }
+        })
+        .collect();
+
+    let manifest = UnityManifest {
+        name: name.to_string(),
+        path: path.to_string(),
+        texture: texture.to_string(),
+        width: atlas.image.width(),
+        height: atlas_height,
+        pixels_per_unit: UNITY_PIXELS_PER_UNIT,
+        sprites,
+    };
+
+    Ok(serde_json::to_vec_pretty(&manifest)?)
+}
+
+/// the animation node as the files of the engine, the frames are packed in one atlas png
+pub fn resolve_engine_export(
+    anim_node: &WzNodeArc,
+    root: Option<&WzNodeArc>,
+    target: EngineTarget,
+) -> Result<EngineExport> {
+    let path = anim_node.read().unwrap().get_full_path();
+    let name = get_engine_export_name(&path);
+    let texture = format!("{}.png", name);
+
+    let (frames, atlas) = resolve_frames(anim_node, root)?;
+
+    let descriptor = match target {
+        EngineTarget::Godot => (
+            format!("{}.tres", name),
+            write_godot_sprite_frames(&name, &texture, &frames, &atlas).into_bytes(),
+        ),
+        EngineTarget::Unity => (
+            format!("{}.json", name),
+            write_unity_manifest(&name, &path, &texture, &frames, &atlas)?,
+        ),
+    };
+
+    let png = encode_png(&DynamicImage::ImageRgba8(atlas.image))?;
+
+    Ok(EngineExport {
+        name,
+        files: vec![(texture, png), descriptor],
+    })
+}
+
+pub fn write_engine_export_zip(export: &EngineExport) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for (file, data) in export.files.iter() {
+        zip.start_file(file.as_str(), options)?;
+        zip.write_all(data)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// write the files into the `output` folder, returns the descriptor path
+pub fn save_engine_export(export: &EngineExport, output: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(output)?;
+
+    for (file, data) in export.files.iter() {
+        std::fs::write(output.join(file), data)?;
+    }
+
+    let descriptor = export
+        .files
+        .last()
+        .map(|(file, _)| output.join(file))
+        .unwrap_or_else(|| output.to_path_buf());
+
+    Ok(descriptor)
+}
@@ -0,0 +1,353 @@
+use std::f64::consts::PI;
+
+use image::{Rgba, RgbaImage};
+use rayon::prelude::*;
+use wz_reader::{WzNode, WzNodeArc};
+
+use super::anim_encode::ComposedAnimation;
+use super::animation::{
+    get_frame_nodes, get_int_at, resolve_animation_frames, resolve_uol_node, Frame,
+};
+use crate::{Error, Result};
+
+pub const DEFAULT_SAMPLE_FPS: u32 = 30;
+const MAX_SAMPLE_FPS: u32 = 120;
+
+pub struct RenderOptions {
+    /// samples per second of the alpha and zoom ramps
+    pub fps: u32,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            fps: DEFAULT_SAMPLE_FPS,
+        }
+    }
+}
+
+/// the ramps of a frame, alpha is 0~255 and zoom is in percent
+struct FrameRamp {
+    a0: i32,
+    a1: i32,
+    z0: i32,
+    z1: i32,
+}
+
+impl FrameRamp {
+    fn is_static(&self) -> bool {
+        self.a0 == self.a1 && self.z0 == self.z1
+    }
+
+    fn get_alpha(&self, progress: f64) -> f64 {
+        lerp(self.a0, self.a1, progress).clamp(0.0, 255.0) / 255.0
+    }
+
+    fn get_scale(&self, progress: f64) -> f64 {
+        lerp(self.z0, self.z1, progress).max(0.0) / 100.0
+    }
+}
+
+/// the container properties, the move ones are the same as the map objects
+struct Playback {
+    zigzag: bool,
+    repeat: bool,
+    move_type: i32,
+    move_w: i32,
+    move_h: i32,
+    /// period of the move in ms
+    move_p: i32,
+    /// period of a full turn in ms, negative turns counterclockwise
+    move_r: i32,
+}
+
+impl Playback {
+    fn is_moving(&self) -> bool {
+        (self.move_type != 0 && self.move_p != 0) || self.move_r != 0
+    }
+
+    /// (x, y) offset at the time since the animation started
+    fn get_offset(&self, time: i32) -> (f64, f64) {
+        if self.move_p == 0 {
+            return (0.0, 0.0);
+        }
+        let phase = 2.0 * PI * time as f64 / self.move_p as f64;
+        let (w, h) = (self.move_w as f64, self.move_h as f64);
+
+        match self.move_type {
+            1 => (w * phase.sin(), 0.0),
+            2 => (0.0, h * phase.sin()),
+            3 => (w * phase.cos(), h * phase.sin()),
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// clockwise radians at the time since the animation started
+    fn get_angle(&self, time: i32) -> f64 {
+        if self.move_r == 0 {
+            return 0.0;
+        }
+        2.0 * PI * time as f64 / self.move_r as f64
+    }
+}
+
+/// one output frame before drawing
+struct Sample {
+    frame: usize,
+    delay: i32,
+    alpha: f64,
+    scale: f64,
+    angle: f64,
+    offset: (f64, f64),
+}
+
+fn lerp(from: i32, to: i32, progress: f64) -> f64 {
+    from as f64 + (to - from) as f64 * progress
+}
+
+fn get_frame_ramp(frame_node: &WzNode) -> FrameRamp {
+    let a0 = get_int_at(frame_node, "a0").unwrap_or(255);
+    let z0 = get_int_at(frame_node, "z0").unwrap_or(100);
+
+    FrameRamp {
+        a0,
+        a1: get_int_at(frame_node, "a1").unwrap_or(a0),
+        z0,
+        z1: get_int_at(frame_node, "z1").unwrap_or(z0),
+    }
+}
+
+fn get_playback(anim_node: &WzNode) -> Playback {
+    let get_flag = |key: &str| get_int_at(anim_node, key).unwrap_or(0) != 0;
+
+    Playback {
+        zigzag: get_flag("zigzag"),
+        repeat: get_flag("repeat"),
+        move_type: get_int_at(anim_node, "moveType").unwrap_or(0),
+        move_w: get_int_at(anim_node, "moveW").unwrap_or(0),
+        move_h: get_int_at(anim_node, "moveH").unwrap_or(0),
+        move_p: get_int_at(anim_node, "moveP").unwrap_or(0),
+        move_r: get_int_at(anim_node, "moveR").unwrap_or(0),
+    }
+}
+
+/// the frame order of one pass, zigzag plays back without repeating both ends
+fn get_sequence(count: usize, zigzag: bool) -> Vec<usize> {
+    let mut sequence = (0..count).collect::<Vec<_>>();
+    if zigzag && count > 2 {
+        sequence.extend((1..count - 1).rev());
+    }
+    sequence
+}
+
+fn resolve_samples(
+    frames: &[Frame],
+    ramps: &[FrameRamp],
+    playback: &Playback,
+    fps: u32,
+) -> Vec<Sample> {
+    let step = (1000 / fps.clamp(1, MAX_SAMPLE_FPS)) as i32;
+    let mut samples = Vec::new();
+    let mut time = 0;
+
+    for index in get_sequence(frames.len(), playback.zigzag) {
+        let delay = frames[index].meta.delay;
+        if delay <= 0 {
+            continue;
+        }
+        let ramp = &ramps[index];
+        let step = if ramp.is_static() && !playback.is_moving() {
+            delay
+        } else {
+            step.max(1)
+        };
+
+        let mut elapsed = 0;
+        while elapsed < delay {
+            let progress = elapsed as f64 / delay as f64;
+            samples.push(Sample {
+                frame: index,
+                delay: step.min(delay - elapsed),
+                alpha: ramp.get_alpha(progress),
+                scale: ramp.get_scale(progress),
+                angle: playback.get_angle(time + elapsed),
+                offset: playback.get_offset(time + elapsed),
+            });
+            elapsed += step;
+        }
+        time += delay;
+    }
+
+    samples
+}
+
+/// (left, top, right, bottom) of the transformed frame relative to the origin
+fn get_sample_rect(frame: &Frame, sample: &Sample) -> (f64, f64, f64, f64) {
+    let (ox, oy) = (frame.meta.origin.0 as f64, frame.meta.origin.1 as f64);
+    let (w, h) = (frame.meta.width as f64, frame.meta.height as f64);
+    let (sin, cos) = sample.angle.sin_cos();
+
+    [(-ox, -oy), (w - ox, -oy), (-ox, h - oy), (w - ox, h - oy)]
+        .iter()
+        .map(|(x, y)| {
+            let (x, y) = (x * sample.scale, y * sample.scale);
+            (
+                x * cos - y * sin + sample.offset.0,
+                x * sin + y * cos + sample.offset.1,
+            )
+        })
+        .fold((f64::MAX, f64::MAX, f64::MIN, f64::MIN), |acc, (x, y)| {
+            (acc.0.min(x), acc.1.min(y), acc.2.max(x), acc.3.max(y))
+        })
+}
+
+fn get_pixel_or_clear(image: &RgbaImage, x: i64, y: i64) -> [f64; 4] {
+    if x < 0 || y < 0 || x >= image.width() as i64 || y >= image.height() as i64 {
+        return [0.0; 4];
+    }
+    let Rgba(pixel) = image.get_pixel(x as u32, y as u32);
+    // premultiply, so the clear pixels don't bleed their color into the edge
+    let alpha = pixel[3] as f64 / 255.0;
+    [
+        pixel[0] as f64 * alpha,
+        pixel[1] as f64 * alpha,
+        pixel[2] as f64 * alpha,
+        pixel[3] as f64,
+    ]
+}
+
+fn sample_bilinear(image: &RgbaImage, x: f64, y: f64) -> [f64; 4] {
+    let (x, y) = (x - 0.5, y - 0.5);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as i64, y0 as i64);
+
+    let corners = [
+        (get_pixel_or_clear(image, x0, y0), (1.0 - fx) * (1.0 - fy)),
+        (get_pixel_or_clear(image, x0 + 1, y0), fx * (1.0 - fy)),
+        (get_pixel_or_clear(image, x0, y0 + 1), (1.0 - fx) * fy),
+        (get_pixel_or_clear(image, x0 + 1, y0 + 1), fx * fy),
+    ];
+
+    let mut result = [0.0; 4];
+    for (pixel, weight) in corners.iter() {
+        for (channel, value) in result.iter_mut().zip(pixel.iter()) {
+            *channel += value * weight;
+        }
+    }
+    result
+}
+
+/// draw the transformed frame, every canvas pixel maps back to the source image
+fn draw_sample(
+    canvas_size: (u32, u32),
+    origin: (f64, f64),
+    source: &RgbaImage,
+    frame: &Frame,
+    sample: &Sample,
+) -> RgbaImage {
+    let mut canvas = RgbaImage::new(canvas_size.0, canvas_size.1);
+    if sample.scale <= 0.0 || sample.alpha <= 0.0 {
+        return canvas;
+    }
+
+    let (ox, oy) = (frame.meta.origin.0 as f64, frame.meta.origin.1 as f64);
+    let (sin, cos) = sample.angle.sin_cos();
+
+    for (x, y, pixel) in canvas.enumerate_pixels_mut() {
+        // the pixel center relative to the frame origin, undo move, rotate then zoom
+        let px = x as f64 + 0.5 - origin.0 - sample.offset.0;
+        let py = y as f64 + 0.5 - origin.1 - sample.offset.1;
+        let sx = (px * cos + py * sin) / sample.scale + ox;
+        let sy = (-px * sin + py * cos) / sample.scale + oy;
+
+        let [r, g, b, a] = sample_bilinear(source, sx, sy);
+        if a <= 0.0 {
+            continue;
+        }
+        let unpremultiply = 255.0 / a;
+        *pixel = Rgba([
+            (r * unpremultiply).round().min(255.0) as u8,
+            (g * unpremultiply).round().min(255.0) as u8,
+            (b * unpremultiply).round().min(255.0) as u8,
+            (a * sample.alpha).round().min(255.0) as u8,
+        ]);
+    }
+
+    canvas
+}
+
+/// render the animation as the client plays it, the alpha and zoom ramps are sampled at `options.fps`
+/// and a zigzag animation is unrolled into one pass
+pub fn render_animation(
+    anim_node: &WzNodeArc,
+    root: Option<&WzNodeArc>,
+    options: &RenderOptions,
+) -> Result<ComposedAnimation> {
+    let frames = resolve_animation_frames(anim_node, root)?;
+    if frames.is_empty() {
+        return Err(Error::NodeTypeMismatch("animation"));
+    }
+
+    let ramps = get_frame_nodes(anim_node)
+        .iter()
+        .map(|frame_node| {
+            let frame_node = resolve_uol_node(frame_node, root).unwrap_or(frame_node.clone());
+            let frame_read = frame_node.read().unwrap();
+            get_frame_ramp(&frame_read)
+        })
+        .collect::<Vec<_>>();
+    let playback = get_playback(&anim_node.read().unwrap());
+
+    let samples = resolve_samples(&frames, &ramps, &playback, options.fps);
+
+    let (left, top, right, bottom) = samples
+        .iter()
+        .map(|sample| get_sample_rect(&frames[sample.frame], sample))
+        .fold((f64::MAX, f64::MAX, f64::MIN, f64::MIN), |acc, rect| {
+            (
+                acc.0.min(rect.0),
+                acc.1.min(rect.1),
+                acc.2.max(rect.2),
+                acc.3.max(rect.3),
+            )
+        });
+    if left >= right || top >= bottom {
+        return Err(Error::ImageProcessingError(
+            "the animation has no visible frame".to_string(),
+        ));
+    }
+
+    let (left, top) = (left.floor(), top.floor());
+    let width = (right.ceil() - left).max(1.0) as u32;
+    let height = (bottom.ceil() - top).max(1.0) as u32;
+    let origin = (-left, -top);
+
+    let sources = frames
+        .par_iter()
+        .map(|frame| frame.image.to_rgba8())
+        .collect::<Vec<_>>();
+
+    let rendered = samples
+        .par_iter()
+        .map(|sample| {
+            let source = &sources[sample.frame];
+            let image = draw_sample(
+                (width, height),
+                origin,
+                source,
+                &frames[sample.frame],
+                sample,
+            );
+            (image, sample.delay)
+        })
+        .collect();
+
+    Ok(ComposedAnimation {
+        width,
+        height,
+        origin: (origin.0 as i32, origin.1 as i32),
+        repeat: playback.repeat,
+        frames: rendered,
+    })
+}
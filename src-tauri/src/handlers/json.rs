@@ -1,6 +1,8 @@
 use serde_json::{to_value, Map, Value, Number}; // 引入 Number
 use wz_reader::{property::WzSubProperty, WzNode, WzObjectType};
 
+use crate::utils::{DUMP_CANVAS_KEY, DUMP_SOUND_KEY};
+
 // === 新增辅助函数：尝试将字符串 Value 转换为数字 Value ===
 fn try_convert_string_to_number(v: Value) -> Value {
     if let Value::String(ref s) = v {
@@ -36,7 +38,8 @@ pub fn to_simple_json(node: &WzNode) -> Result<serde_json::Value, serde_json::Er
     }
 
     let mut json = Map::new();
-    let mut generate_extra_path = false;
+    // dump canvas 也需要 path 才能拿到图片
+    let mut generate_extra_path = node.at(DUMP_CANVAS_KEY).is_some();
 
     match &node.object_type {
         WzObjectType::Property(WzSubProperty::PNG(inner)) => {
@@ -63,9 +66,14 @@ pub fn to_simple_json(node: &WzNode) -> Result<serde_json::Value, serde_json::Er
     }
 
     for (name, value) in node.children.iter() {
+        // dump 的 base64 数据不输出
+        let name = name.to_string();
+        if name == DUMP_CANVAS_KEY || name == DUMP_SOUND_KEY {
+            continue;
+        }
         let child = value.read().unwrap();
         // 递归调用会自动处理子节点的类型转换
-        json.insert(name.to_string(), to_simple_json(&child)?);
+        json.insert(name, to_simple_json(&child)?);
     }
 
     if generate_extra_path && !json.contains_key("_outlink") {
@@ -18,7 +18,7 @@ pub fn resolve_map_id_map(root: &WzNodeArc) -> Result<HashMap<String, (String, S
     let root_read = root.read().unwrap();
     let map_string_node = root_read
         .at_path(MAP_STRING_PATH)
-        .ok_or(Error::NodeNotFound)?;
+        .ok_or_else(|| Error::node_not_found(&root_read, MAP_STRING_PATH))?;
 
     node_util::parse_node(&map_string_node)?;
 
@@ -45,7 +45,9 @@ pub fn resolve_map_string(root: &WzNodeArc) -> Result<Vec<(String, String, Strin
 
     let root_read = root.read().unwrap();
     let mut result = vec![];
-    let folders_node = root_read.at_path(MAP_PATH).ok_or(Error::NodeNotFound)?;
+    let folders_node = root_read
+        .at_path(MAP_PATH)
+        .ok_or_else(|| Error::node_not_found(&root_read, MAP_PATH))?;
 
     let empty_node_name = String::from("null");
 
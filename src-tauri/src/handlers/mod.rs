@@ -1,28 +1,48 @@
+mod anim_encode;
+pub mod animation;
+mod batch_image;
 mod chair;
+mod duplicate;
+mod engine_export;
 mod equip;
+mod frame_render;
 mod image_map;
 mod item;
 pub mod json;
 mod map;
 mod mount;
-mod mount_skill_id;
+mod node_info;
 pub mod path;
 mod png;
+mod search;
 mod skill;
+mod skill_export;
+mod skill_timeline;
 mod smap;
 mod string;
 pub mod webp;
+mod xml_export;
 mod zmap;
 pub mod audio; // <--- 必须添加这行：声明 audio 模块存在 (对应文件 handlers/audio.rs)
 
+pub use anim_encode::*;
+pub use batch_image::*;
 pub use chair::*;
+pub use duplicate::*;
+pub use engine_export::*;
 pub use equip::*;
+pub use frame_render::*;
 pub use image_map::*;
 pub use map::*;
 pub use mount::*;
+pub use node_info::*;
 pub use png::*;
+pub use search::*;
 pub use skill::*;
+pub use skill_export::*;
+pub use skill_timeline::*;
 pub use smap::*;
 pub use string::*;
+pub use xml_export::*;
 pub use zmap::*;
 pub use audio::*; // <--- 然后才能导出
\ No newline at end of file
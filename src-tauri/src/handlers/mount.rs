@@ -1,104 +1,319 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
-use wz_reader::{property::resolve_string_from_node, util::node_util, WzNodeArc, WzNodeCast};
+use std::path::Path;
+use std::sync::Arc;
 
-use super::mount_skill_id::MOUNT_SKILL_ID_MAP;
-use super::path::{MOUNT_PATH, MOUNT_SKILL_PATH, MOUNT_STRING_PATH, SKILL_STRING_PATH};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+use wz_reader::{property::resolve_string_from_node, util::node_util, WzNode, WzNodeArc};
 
-use crate::{Error, Result};
+use super::animation::get_int_at;
+use super::path::{MOUNT_PATH, MOUNT_SKILL_PATH, MOUNT_STRING_PATH, SKILL_PATH, SKILL_STRING_PATH};
 
-fn resolve_mount_id_map(root: &WzNodeArc) -> HashMap<String, String> {
-    let mut mount_id_map = HashMap::from_iter(
-        MOUNT_SKILL_ID_MAP
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.to_string())),
-    );
+use crate::memory::ImageTracker;
+use crate::{Error, MountSkillCache, Result};
 
-    let root_read = root.read().unwrap();
-    let mount_mapping = root_read.at_path(MOUNT_SKILL_PATH);
+/// the user edited mapping in the app data dir, like `{ "1932016": "80001022" }`
+pub const MOUNT_SKILL_OVERRIDES_FILE: &'static str = "mount-skill-overrides.json";
+
+/// the keys of a skill point to the mount it rides
+const VEHICLE_KEYS: [&str; 2] = ["vehicleID", "tamingMob"];
+
+/// where the skill of a mount comes from, the later one wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MountSkillSource {
+    Skill,
+    RidingSkillInfo,
+    Override,
+}
 
-    if mount_mapping.is_none() {
-        return mount_id_map;
+/// mount id -> (skill id, source)
+pub type MountSkillMap = HashMap<String, (String, MountSkillSource)>;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MountEntry {
+    pub id: String,
+    pub name: Option<String>,
+    pub skill_id: Option<String>,
+    pub skill_name: Option<String>,
+    pub source: Option<MountSkillSource>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MountSkillReport {
+    pub total: usize,
+    pub mapped: usize,
+    pub overridden: usize,
+    /// the mounts without skill, add them to the overrides file
+    pub unmapped: Vec<MountEntry>,
+}
+
+/// mount id without the leading zeros, the same as the keys of the mount list
+fn normalize_mount_id(id: &str) -> String {
+    id.trim_end_matches(".img")
+        .trim_start_matches('0')
+        .to_string()
+}
+
+fn get_vehicle_id(node: &WzNode) -> Option<String> {
+    VEHICLE_KEYS.iter().find_map(|key| {
+        get_int_at(node, key)
+            .filter(|id| *id > 0)
+            .map(|id| id.to_string())
+    })
+}
+
+/// read the overrides, a missing file means no override
+pub fn load_mount_skill_overrides(path: &Path) -> Result<HashMap<String, String>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
     }
-    let mount_mapping = mount_mapping.unwrap();
 
-    let _ = node_util::parse_node(&mount_mapping);
+    let text = std::fs::read_to_string(path)?;
+    let overrides = serde_json::from_str::<HashMap<String, Value>>(&text)?;
 
-    for (skill_id, node) in mount_mapping.read().unwrap().children.iter() {
-        let vehicle_id = node.read().unwrap().at("vehicleID");
-        if vehicle_id.is_none() {
-            continue;
-        }
-        let vehicle_id = vehicle_id.unwrap();
-        let read = vehicle_id.read().unwrap();
-        if let Some(id) = read.try_as_int() {
-            let id = id.to_string();
-            mount_id_map.insert(id, skill_id.to_string());
-        } else if let Some(id) = read.try_as_string() {
-            let string = id.get_string().ok();
-            if let Some(string) = string {
-                mount_id_map.insert(string, skill_id.to_string());
+    // the ids can be written as number or string
+    Ok(overrides
+        .into_iter()
+        .filter_map(|(mount_id, skill_id)| {
+            let skill_id = match skill_id {
+                Value::String(id) => id,
+                Value::Number(id) => id.to_string(),
+                _ => return None,
+            };
+            Some((normalize_mount_id(&mount_id), skill_id))
+        })
+        .collect())
+}
+
+/// (mount id, skill id) of every skill with a `vehicleID` or `tamingMob` in `Skill/*.img`
+fn scan_skill_vehicle_ids(root: &WzNodeArc, images: &ImageTracker) -> Vec<(String, String)> {
+    let skill_folder = root.read().unwrap().at(SKILL_PATH);
+    let Some(skill_folder) = skill_folder else {
+        return Vec::new();
+    };
+
+    let job_images = skill_folder
+        .read()
+        .unwrap()
+        .children
+        .iter()
+        .filter(|(name, _)| name.as_bytes().first().map_or(false, u8::is_ascii_digit))
+        .map(|(_, node)| node.clone())
+        .collect::<Vec<_>>();
+
+    let mut result = job_images
+        .par_iter()
+        .flat_map_iter(|job_image| {
+            if node_util::parse_node(job_image).is_err() {
+                return Vec::new();
             }
+            images.track(job_image);
+            let skills = job_image.read().unwrap().at("skill");
+            let Some(skills) = skills else {
+                return Vec::new();
+            };
+
+            let skills_read = skills.read().unwrap();
+            skills_read
+                .children
+                .iter()
+                .filter_map(|(skill_id, skill)| {
+                    let skill_read = skill.read().unwrap();
+                    let vehicle_id = get_vehicle_id(&skill_read).or_else(|| {
+                        let common = skill_read.at("common")?;
+                        let common_read = common.read().unwrap();
+                        get_vehicle_id(&common_read)
+                    })?;
+                    Some((vehicle_id, skill_id.to_string()))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // a mount used by many skills maps to the lowest skill id, the lowest one is inserted last
+    result.sort_by_cached_key(|(_, skill_id)| Reverse(skill_id.parse::<u64>().unwrap_or(u64::MAX)));
+    result
+}
+
+fn scan_riding_skill_info(root: &WzNodeArc, images: &ImageTracker) -> Vec<(String, String)> {
+    let mount_mapping = root.read().unwrap().at_path(MOUNT_SKILL_PATH);
+    let Some(mount_mapping) = mount_mapping else {
+        return Vec::new();
+    };
+
+    let _ = node_util::parse_node(&mount_mapping);
+    images.track(&mount_mapping);
+
+    let mapping_read = mount_mapping.read().unwrap();
+    mapping_read
+        .children
+        .iter()
+        .filter_map(|(skill_id, node)| {
+            let vehicle_id = get_vehicle_id(&node.read().unwrap())?;
+            Some((vehicle_id, skill_id.to_string()))
+        })
+        .collect()
+}
+
+/// mount id -> (skill id, source) of the data, without the overrides
+fn scan_mount_skill_map(root: &WzNodeArc, images: &ImageTracker) -> MountSkillMap {
+    let sources = [
+        (
+            scan_skill_vehicle_ids(root, images),
+            MountSkillSource::Skill,
+        ),
+        (
+            scan_riding_skill_info(root, images),
+            MountSkillSource::RidingSkillInfo,
+        ),
+    ];
+
+    // the scan parses every skill image, release them when over the budget
+    images.enforce();
+
+    let mut mount_skill_map = HashMap::new();
+
+    for (pairs, source) in sources {
+        for (mount_id, skill_id) in pairs {
+            mount_skill_map.insert(normalize_mount_id(&mount_id), (skill_id, source));
         }
     }
 
-    mount_id_map
+    mount_skill_map
 }
 
-pub fn resolve_mount_string(root: &WzNodeArc) -> Result<Vec<(String, String)>> {
-    let skill_id_map = resolve_mount_id_map(root);
+/// the scanned map of the loaded root, scan it at the first call
+fn get_scanned_mount_skill_map(
+    root: &WzNodeArc,
+    cache: &MountSkillCache,
+    images: &ImageTracker,
+) -> Arc<MountSkillMap> {
+    if let Some(scanned) = cache.read().unwrap().as_ref() {
+        return Arc::clone(scanned);
+    }
+
+    // hold the lock while scanning, the other callers wait instead of scanning again
+    let mut cache = cache.write().unwrap();
+    let scanned = cache.get_or_insert_with(|| Arc::new(scan_mount_skill_map(root, images)));
 
+    Arc::clone(scanned)
+}
+
+/// mount id -> (skill id, source), the overrides replace what the data says
+pub fn resolve_mount_skill_map(
+    root: &WzNodeArc,
+    cache: &MountSkillCache,
+    overrides: &HashMap<String, String>,
+    images: &ImageTracker,
+) -> MountSkillMap {
+    let mut mount_skill_map = (*get_scanned_mount_skill_map(root, cache, images)).clone();
+
+    for (mount_id, skill_id) in overrides.iter() {
+        mount_skill_map.insert(
+            mount_id.clone(),
+            (skill_id.clone(), MountSkillSource::Override),
+        );
+    }
+
+    mount_skill_map
+}
+
+fn get_name_at(string_node: &WzNodeArc, id: &str) -> Option<String> {
+    string_node
+        .read()
+        .unwrap()
+        .at(id)
+        .and_then(|string| string.read().unwrap().at("name"))
+        .and_then(|string| resolve_string_from_node(&string).ok())
+}
+
+pub fn resolve_mount_entries(
+    root: &WzNodeArc,
+    mount_skill_map: &MountSkillMap,
+) -> Result<Vec<MountEntry>> {
     let root_read = root.read().unwrap();
-    let mut result = vec![];
-    let mount_folders_node = root_read.at_path(MOUNT_PATH).ok_or(Error::NodeNotFound)?;
+    let mount_folders_node = root_read
+        .at_path(MOUNT_PATH)
+        .ok_or_else(|| Error::node_not_found(&root_read, MOUNT_PATH))?;
     let string_node = root_read
         .at_path(MOUNT_STRING_PATH)
-        .ok_or(Error::NodeNotFound)?;
+        .ok_or_else(|| Error::node_not_found(&root_read, MOUNT_STRING_PATH))?;
     let skill_string_node = root_read
         .at_path(SKILL_STRING_PATH)
-        .ok_or(Error::NodeNotFound)?;
-    let empty_node_name = String::from("null");
+        .ok_or_else(|| Error::node_not_found(&root_read, SKILL_STRING_PATH))?;
 
     node_util::parse_node(&skill_string_node)?;
 
-    for mount_id_key in mount_folders_node
+    let mut result = mount_folders_node
         .read()
         .unwrap()
         .children
         .keys()
         .filter(|key| !key.starts_with("0191") && !key.starts_with("0198") && key.ends_with(".img"))
-    {
-        let mount_id = mount_id_key
-            .trim_start_matches('0')
-            .trim_end_matches(".img");
-
-        let mut name = empty_node_name.clone();
-
-        if let Some(string) = string_node
-            .read()
-            .unwrap()
-            .at(mount_id)
-            .and_then(|string| string.read().unwrap().at("name"))
-            .and_then(|string| resolve_string_from_node(&string).ok())
-        {
-            name = string;
-        }
+        .map(|mount_id_key| {
+            let mount_id = normalize_mount_id(mount_id_key);
+            let skill = mount_skill_map.get(&mount_id);
 
-        if let Some(skill_id) = skill_id_map.get(mount_id) {
-            if let Some(string) = skill_string_node
-                .read()
-                .unwrap()
-                .at(skill_id)
-                .and_then(|string| string.read().unwrap().at("name"))
-                .and_then(|string| resolve_string_from_node(&string).ok())
-            {
-                name = string;
+            MountEntry {
+                name: get_name_at(&string_node, &mount_id),
+                skill_name: skill
+                    .and_then(|(skill_id, _)| get_name_at(&skill_string_node, skill_id)),
+                skill_id: skill.map(|(skill_id, _)| skill_id.clone()),
+                source: skill.map(|(_, source)| *source),
+                id: mount_id,
             }
-        }
-
-        result.push((mount_id.to_string(), name));
-    }
+        })
+        .collect::<Vec<_>>();
 
-    result.sort_by(|a, b| a.0.cmp(&b.0));
+    result.sort_by(|a, b| a.id.cmp(&b.id));
 
     Ok(result)
 }
+
+/// (id, name), the skill name is used when the mount has one
+pub fn resolve_mount_string(
+    root: &WzNodeArc,
+    mount_skill_map: &MountSkillMap,
+) -> Result<Vec<(String, String)>> {
+    let entries = resolve_mount_entries(root, mount_skill_map)?;
+
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let name = entry
+                .skill_name
+                .or(entry.name)
+                .unwrap_or_else(|| String::from("null"));
+            (entry.id, name)
+        })
+        .collect())
+}
+
+pub fn resolve_mount_skill_report(
+    root: &WzNodeArc,
+    mount_skill_map: &MountSkillMap,
+) -> Result<MountSkillReport> {
+    let entries = resolve_mount_entries(root, mount_skill_map)?;
+
+    let total = entries.len();
+    let overridden = entries
+        .iter()
+        .filter(|entry| entry.source == Some(MountSkillSource::Override))
+        .count();
+    let unmapped = entries
+        .into_iter()
+        .filter(|entry| entry.skill_id.is_none())
+        .collect::<Vec<_>>();
+
+    Ok(MountSkillReport {
+        total,
+        mapped: total - unmapped.len(),
+        overridden,
+        unmapped,
+    })
+}
@@ -0,0 +1,168 @@
+use std::cmp::Ordering;
+
+use serde_json::Value;
+use wz_reader::{
+    property::{string::resolve_string_from_node, WzValue},
+    util::node_util,
+    WzNode, WzNodeArc, WzNodeCast, WzObjectType,
+};
+
+use super::search::get_node_value_text;
+use crate::models::{LinkInfo, LinkKind, NodeInfo, NodeInfoPage, PngInfo, SoundInfo};
+use crate::Result;
+
+const VALUE_PREVIEW_LENGTH: usize = 100;
+
+/// compare the digit runs by number, so `2` < `10` and `a2` < `a10`
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ac), Some(bc)) if ac.is_ascii_digit() && bc.is_ascii_digit() => {
+                let a_digits = take_digits(&mut a_chars);
+                let b_digits = take_digits(&mut b_chars);
+                let a_trimmed = a_digits.trim_start_matches('0');
+                let b_trimmed = b_digits.trim_start_matches('0');
+
+                let ordering = a_trimmed
+                    .len()
+                    .cmp(&b_trimmed.len())
+                    .then_with(|| a_trimmed.cmp(b_trimmed))
+                    .then_with(|| a_digits.len().cmp(&b_digits.len()));
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(ac), Some(bc)) => {
+                if ac != bc {
+                    return ac.cmp(&bc);
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+        digits.push(c);
+    }
+    digits
+}
+
+/// children of the node in natural order
+pub fn get_natural_sorted_children(node: &WzNode) -> Vec<(String, WzNodeArc)> {
+    let mut children = node
+        .children
+        .iter()
+        .map(|(name, child)| (name.to_string(), child.clone()))
+        .collect::<Vec<_>>();
+
+    children.sort_by(|a, b| natural_cmp(&a.0, &b.0));
+
+    children
+}
+
+fn get_value_preview(node: &WzNode) -> Option<Value> {
+    match &node.object_type {
+        WzObjectType::Value(WzValue::Vector(v)) => Some(Value::from(vec![v.0, v.1])),
+        WzObjectType::Value(WzValue::Short(v)) => Some(Value::from(*v)),
+        WzObjectType::Value(WzValue::Int(v)) => Some(Value::from(*v)),
+        WzObjectType::Value(WzValue::Long(v)) => Some(Value::from(*v)),
+        WzObjectType::Value(WzValue::Float(v)) => Some(Value::from(*v)),
+        WzObjectType::Value(WzValue::Double(v)) => Some(Value::from(*v)),
+        _ => {
+            let text = get_node_value_text(node)?;
+            let preview = if text.chars().count() > VALUE_PREVIEW_LENGTH {
+                format!("{}…", text.chars().take(VALUE_PREVIEW_LENGTH).collect::<String>())
+            } else {
+                text
+            };
+            Some(Value::String(preview))
+        }
+    }
+}
+
+fn get_link_info(node: &WzNode) -> Option<LinkInfo> {
+    if let Some(uol) = node.try_as_uol() {
+        let target = uol.get_string().ok()?;
+        return Some(LinkInfo {
+            kind: LinkKind::Uol,
+            target: node_util::get_resolved_uol_path(&node.get_full_path(), &target),
+        });
+    }
+
+    [("_inlink", LinkKind::Inlink), ("_outlink", LinkKind::Outlink)]
+        .into_iter()
+        .find_map(|(key, kind)| {
+            let target = resolve_string_from_node(&node.at(key)?).ok()?;
+            Some(LinkInfo { kind, target })
+        })
+}
+
+pub fn resolve_node_info(node: &WzNode, pack: Option<String>) -> Result<NodeInfo> {
+    let png = node.try_as_png().map(|png| PngInfo {
+        width: png.width,
+        height: png.height,
+        format: png.format(),
+    });
+
+    let sound = node.try_as_sound().map(|sound| SoundInfo {
+        duration: sound.duration,
+        codec: format!("{:?}", sound.sound_type),
+    });
+
+    Ok(NodeInfo {
+        name: node.name.to_string(),
+        _type: serde_json::to_string(&node.object_type)?,
+        has_child: !node.children.is_empty(),
+        pack,
+        value: get_value_preview(node),
+        png,
+        sound,
+        link: get_link_info(node),
+    })
+}
+
+/// the children info in natural order, `get_pack` find the pack source by child path
+pub fn resolve_children_info<F>(
+    node: &WzNode,
+    path: &str,
+    offset: usize,
+    limit: Option<usize>,
+    get_pack: F,
+) -> Result<NodeInfoPage>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let children = get_natural_sorted_children(node);
+    let total = children.len();
+
+    let items = children
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|(name, child)| {
+            let child_path = if path.is_empty() {
+                name
+            } else {
+                format!("{}/{}", path, name)
+            };
+            resolve_node_info(&child.read().unwrap(), get_pack(&child_path))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(NodeInfoPage {
+        total,
+        offset,
+        items,
+    })
+}
@@ -1,3 +1,7 @@
+use wz_reader::{node, WzNodeArc};
+
+use crate::{Error, Result};
+
 pub const ZMAP_PATH: &'static str = "zmap.img";
 pub const SMAP_PATH: &'static str = "smap.img";
 pub const CHARACTER_ITEM_PATH: &'static str = "Character";
@@ -24,6 +28,17 @@ pub const CASH_CHAIR_STRING_PATH: &'static str = "String/Cash.img";
 
 pub const SKILL_PATH: &'static str = "Skill";
 pub const SKILL_STRING_PATH: &'static str = "String/Skill.img";
+pub const SKILL_SOUND_PATH: &'static str = "Sound/Skill.img";
 
 pub const MAP_PATH: &'static str = "Map/Map"; // Map0...Map9
 pub const MAP_STRING_PATH: &'static str = "String/Map.img";
+
+/// like `at_path_parsed`, but the not found error tells how deep the path resolved
+pub fn get_node_parsed(root: &WzNodeArc, path: &str) -> Result<WzNodeArc> {
+    let result = root.read().unwrap().at_path_parsed(path);
+
+    result.map_err(|e| match e {
+        node::Error::NodeNotFound => Error::node_not_found(&root.read().unwrap(), path),
+        e => Error::from(e),
+    })
+}
@@ -1,4 +1,4 @@
-use crate::{Error, Result};
+use crate::{utils, Error, Result};
 
 use image::DynamicImage;
 use wz_reader::{property::string, util::node_util, WzNodeArc, WzNodeCast};
@@ -20,7 +20,11 @@ fn get_node_from_image_node(image_node: &WzNodeArc, path: &str) -> Option<WzNode
 pub fn resolve_png(node: &WzNodeArc, root: Option<&WzNodeArc>) -> Result<DynamicImage> {
     let node_read = node.read().unwrap();
 
-    if let Some(png) = node_read.try_as_png() {
+    let png = node_read.try_as_png();
+    // the canvas of a dump links the same way as a png
+    let is_dump_canvas = node_read.at(utils::DUMP_CANVAS_KEY).is_some();
+
+    if png.is_some() || is_dump_canvas {
         let inlink_target = node_read
             .at("_inlink")
             .and_then(|node| string::resolve_string_from_node(&node).ok())
@@ -44,8 +48,12 @@ pub fn resolve_png(node: &WzNodeArc, root: Option<&WzNodeArc>) -> Result<Dynamic
         if let Some(target) = outlink_target {
             return resolve_png(&target, root);
         }
+    }
 
+    if let Some(png) = png {
         png.extract_png().map_err(Error::from)
+    } else if let Some(image) = utils::resolve_dump_canvas(&node_read) {
+        image
     } else {
         Err(Error::NodeTypeMismatch("png"))
     }
@@ -56,7 +64,8 @@ pub fn resolve_png_unparsed(
     path: &str,
     root: Option<&WzNodeArc>,
 ) -> Result<DynamicImage> {
-    let target = get_node_from_image_node(&image_node, &path).ok_or(Error::NodeNotFound)?;
+    let target = get_node_from_image_node(&image_node, &path)
+        .ok_or_else(|| Error::node_not_found(&image_node.read().unwrap(), path))?;
 
     let node_read = target.read().unwrap();
 
@@ -80,8 +89,8 @@ pub fn resolve_png_unparsed(
             .and_then(|node| string::resolve_string_from_node(&node).ok());
 
         if let (Some(root_node), Some(link)) = (root, outlink) {
-            let (image_node, rest_path) =
-                node_util::get_image_node_from_path(root_node, &link).ok_or(Error::NodeNotFound)?;
+            let (image_node, rest_path) = node_util::get_image_node_from_path(root_node, &link)
+                .ok_or_else(|| Error::node_not_found(&root_node.read().unwrap(), &link))?;
 
             return resolve_png_unparsed(&image_node, &rest_path, root);
         }
@@ -93,7 +102,8 @@ pub fn resolve_png_unparsed(
 }
 
 pub fn resolve_png_form_root(root: &WzNodeArc, path: &str) -> Result<DynamicImage> {
-    let target = node_util::get_node_without_parse(root, path).ok_or(Error::NodeNotFound)?;
+    let target = node_util::get_node_without_parse(root, path)
+        .ok_or_else(|| Error::node_not_found(&root.read().unwrap(), path))?;
 
     resolve_png(&target, Some(root))
 }
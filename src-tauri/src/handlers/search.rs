@@ -0,0 +1,247 @@
+use regex::Regex;
+use serde::Serialize;
+use wz_reader::{
+    property::{WzSubProperty, WzValue},
+    util::node_util,
+    WzNode, WzNodeArc, WzNodeCast, WzObjectType,
+};
+
+use crate::memory::SharedImageTracker;
+use crate::{Error, Result};
+
+/// how many characters of string value to show in the result
+const VALUE_PREVIEW_LENGTH: usize = 200;
+
+pub struct SearchOptions {
+    pub name: Option<Regex>,
+    /// lowercase type names, like `png`, `sound`, `uol`, `string`, `int`, `vector`
+    pub types: Option<Vec<String>>,
+    pub value: Option<String>,
+    /// the depth relative to the search root, 0 only match the root itself
+    pub max_depth: Option<usize>,
+    /// parse the unparsed images along the way, otherwise skip them
+    pub parse_images: bool,
+    /// the parsed images are tracked, so the memory budget applies while searching
+    pub images: Option<SharedImageTracker>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub path: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub _type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// `*` match any characters and `?` match one character
+pub fn glob_to_regex(glob: &str) -> Result<Regex> {
+    let mut pattern = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+
+    Regex::new(&pattern).map_err(|e| Error::InvalidSearchPattern(e.to_string()))
+}
+
+pub fn build_name_matcher(pattern: &str, is_regex: bool) -> Result<Regex> {
+    if is_regex {
+        Regex::new(pattern).map_err(|e| Error::InvalidSearchPattern(e.to_string()))
+    } else {
+        glob_to_regex(pattern)
+    }
+}
+
+pub fn get_node_type_name(node: &WzNode) -> &'static str {
+    match &node.object_type {
+        WzObjectType::Value(value) => match value {
+            WzValue::Null => "null",
+            WzValue::Short(_) => "short",
+            WzValue::Int(_) => "int",
+            WzValue::Long(_) => "long",
+            WzValue::Float(_) => "float",
+            WzValue::Double(_) => "double",
+            WzValue::Vector(_) => "vector",
+            WzValue::ParsedString(_) => "string",
+            _ if node.try_as_uol().is_some() => "uol",
+            _ if node.try_as_string().is_some() => "string",
+            _ => "raw",
+        },
+        WzObjectType::Property(WzSubProperty::PNG(_)) => "png",
+        WzObjectType::Property(WzSubProperty::Sound(_)) => "sound",
+        WzObjectType::Property(WzSubProperty::Property) => "property",
+        WzObjectType::Property(_) => "convex",
+        _ if node.try_as_file().is_some() => "file",
+        _ if node.try_as_image().is_some() => "image",
+        _ => "folder",
+    }
+}
+
+/// the text of string and number nodes, None for the others
+pub fn get_node_value_text(node: &WzNode) -> Option<String> {
+    match &node.object_type {
+        WzObjectType::Value(value) => match value {
+            WzValue::Short(v) => Some(v.to_string()),
+            WzValue::Int(v) => Some(v.to_string()),
+            WzValue::Long(v) => Some(v.to_string()),
+            WzValue::Float(v) => Some(v.to_string()),
+            WzValue::Double(v) => Some(v.to_string()),
+            WzValue::ParsedString(v) => Some(v.clone()),
+            _ => node
+                .try_as_uol()
+                .or_else(|| node.try_as_string())
+                .and_then(|s| s.get_string().ok()),
+        },
+        _ => None,
+    }
+}
+
+fn get_value_preview(text: &str) -> String {
+    if text.chars().count() > VALUE_PREVIEW_LENGTH {
+        let preview = text.chars().take(VALUE_PREVIEW_LENGTH).collect::<String>();
+        format!("{}…", preview)
+    } else {
+        text.to_string()
+    }
+}
+
+/// number value compare the number, string value is a case insensitive substring match
+fn is_value_matched(node: &WzNode, expected: &str) -> Option<String> {
+    let text = get_node_value_text(node)?;
+
+    let is_number = matches!(
+        node.object_type,
+        WzObjectType::Value(
+            WzValue::Short(_)
+                | WzValue::Int(_)
+                | WzValue::Long(_)
+                | WzValue::Float(_)
+                | WzValue::Double(_)
+        )
+    );
+
+    let is_matched = if is_number {
+        match (text.parse::<f64>(), expected.trim().parse::<f64>()) {
+            (Ok(actual), Ok(expected)) => actual == expected,
+            _ => false,
+        }
+    } else {
+        text.to_lowercase().contains(&expected.to_lowercase())
+    };
+
+    is_matched.then_some(text)
+}
+
+fn match_node(node: &WzNode, path: &str, options: &SearchOptions) -> Option<SearchResult> {
+    let name = node.name.to_string();
+
+    if let Some(matcher) = &options.name {
+        if !matcher.is_match(&name) {
+            return None;
+        }
+    }
+
+    let _type = get_node_type_name(node);
+
+    if let Some(types) = &options.types {
+        if !types.iter().any(|t| t == _type) {
+            return None;
+        }
+    }
+
+    let value = match &options.value {
+        Some(expected) => Some(is_value_matched(node, expected)?),
+        None => get_node_value_text(node),
+    };
+
+    Some(SearchResult {
+        path: path.to_string(),
+        name,
+        _type,
+        value: value.map(|v| get_value_preview(&v)),
+    })
+}
+
+/// depth first search in name order, stop when `on_match` return false
+pub fn search_nodes<F>(
+    node: &WzNodeArc,
+    path: &str,
+    options: &SearchOptions,
+    on_match: &mut F,
+) -> Result<bool>
+where
+    F: FnMut(SearchResult) -> bool,
+{
+    search_node(node, path, 0, options, on_match)
+}
+
+fn search_node<F>(
+    node: &WzNodeArc,
+    path: &str,
+    depth: usize,
+    options: &SearchOptions,
+    on_match: &mut F,
+) -> Result<bool>
+where
+    F: FnMut(SearchResult) -> bool,
+{
+    let is_unparsed_image = {
+        let node_read = node.read().unwrap();
+        node_read.try_as_image().is_some() && node_read.children.is_empty()
+    };
+
+    let is_parsed_here = is_unparsed_image && options.parse_images;
+    if is_parsed_here {
+        node_util::parse_node(node)?;
+        if let Some(images) = &options.images {
+            images.track(node);
+        }
+    }
+
+    let children = {
+        let node_read = node.read().unwrap();
+
+        if let Some(result) = match_node(&node_read, path, options) {
+            if !on_match(result) {
+                return Ok(false);
+            }
+        }
+
+        if options.max_depth.map_or(false, |max| depth >= max) {
+            return Ok(true);
+        }
+
+        let mut children = node_read
+            .children
+            .iter()
+            .map(|(name, child)| (name.to_string(), child.clone()))
+            .collect::<Vec<_>>();
+        children.sort_by(|a, b| a.0.cmp(&b.0));
+        children
+    };
+
+    for (name, child) in children {
+        let child_path = if path.is_empty() {
+            name
+        } else {
+            format!("{}/{}", path, name)
+        };
+        if !search_node(&child, &child_path, depth + 1, options, on_match)? {
+            return Ok(false);
+        }
+    }
+
+    // done with the image, the older ones can be released now
+    if let (true, Some(images)) = (is_parsed_here, &options.images) {
+        images.enforce();
+    }
+
+    Ok(true)
+}
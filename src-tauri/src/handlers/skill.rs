@@ -12,10 +12,12 @@ pub fn resolve_skill_string(root: &WzNodeArc) -> Result<Vec<(String, String, Str
     let mut result = vec![];
     let (skill_folder_node, string_node) = {
         let root_read = root.read().unwrap();
-        let skill_folder_node = root_read.at_path(SKILL_PATH).ok_or(Error::NodeNotFound)?;
+        let skill_folder_node = root_read
+            .at_path(SKILL_PATH)
+            .ok_or_else(|| Error::node_not_found(&root_read, SKILL_PATH))?;
         let string_node = root_read
             .at_path(SKILL_STRING_PATH)
-            .ok_or(Error::NodeNotFound)?;
+            .ok_or_else(|| Error::node_not_found(&root_read, SKILL_STRING_PATH))?;
         (skill_folder_node, string_node)
     };
     node_util::parse_node(&string_node)?;
@@ -0,0 +1,372 @@
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use image::{DynamicImage, ImageFormat};
+use rayon::prelude::*;
+use serde::Serialize;
+use wz_reader::{property::resolve_string_from_node, WzNodeArc, WzNodeCast};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use super::animation::{find_animation_nodes, get_int_at, resolve_animation_frames, FrameMeta};
+use super::audio::resolve_sound_buffer;
+use super::path::{MOUNT_PATH, SKILL_PATH, SKILL_SOUND_PATH, SKILL_STRING_PATH};
+use super::png::resolve_png;
+
+use crate::memory::ImageTracker;
+use crate::utils::DUMP_SOUND_KEY;
+use crate::{Error, Result};
+
+const SKILL_ICON_NAMES: [&str; 3] = ["icon", "iconMouseOver", "iconDisabled"];
+
+pub const MANIFEST_FILE_NAME: &'static str = "manifest.json";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillManifest {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub icons: Vec<String>,
+    pub animations: Vec<AnimationManifest>,
+    pub sounds: Vec<SoundManifest>,
+    /// animations that failed to resolve, usually a broken _outlink
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationManifest {
+    pub name: String,
+    pub path: String,
+    pub z: Option<i32>,
+    pub frames: Vec<FrameManifest>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameManifest {
+    pub file: String,
+    #[serde(flatten)]
+    pub meta: FrameMeta,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoundManifest {
+    pub name: String,
+    pub file: String,
+    pub path: String,
+}
+
+pub struct SkillBundle {
+    pub manifest: SkillManifest,
+    /// (relative file path, file content)
+    pub files: Vec<(String, Vec<u8>)>,
+}
+
+/// the skill id is also a file name of the bundle, so only digits are allowed
+pub fn validate_skill_id(skill_id: &str) -> Result<()> {
+    if skill_id.is_empty() || !skill_id.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::InvalidSkillId(skill_id.to_string()));
+    }
+    Ok(())
+}
+
+/// the skill image is the skill id without last 4 digits, like 23121000 -> 2312.img, 1001003 -> 100.img
+pub fn get_skill_image_name(skill_id: &str) -> String {
+    let prefix_len = skill_id.chars().count().saturating_sub(4);
+    let prefix = skill_id.chars().take(prefix_len).collect::<String>();
+    format!("{:0>3}.img", prefix)
+}
+
+pub fn get_skill_node(root: &WzNodeArc, skill_id: &str) -> Result<WzNodeArc> {
+    validate_skill_id(skill_id)?;
+
+    let root_read = root.read().unwrap();
+
+    let path = format!(
+        "{}/{}/skill/{}",
+        SKILL_PATH,
+        get_skill_image_name(skill_id),
+        skill_id
+    );
+
+    if let Ok(node) = root_read.at_path_parsed(&path) {
+        return Ok(node);
+    }
+
+    // some skills are not in the folder we expect, search the job images already parsed.
+    // parsing every job image for an unknown id is too expensive
+    let skill_folder_node = root_read
+        .at(SKILL_PATH)
+        .ok_or_else(|| Error::node_not_found(&root_read, SKILL_PATH))?;
+    let job_folders = skill_folder_node
+        .read()
+        .unwrap()
+        .children
+        .keys()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+
+    job_folders
+        .iter()
+        .find_map(|folder| {
+            root_read.at_path(&format!("{}/{}/skill/{}", SKILL_PATH, folder, skill_id))
+        })
+        .ok_or_else(|| Error::node_not_found(&root_read, &path))
+}
+
+/// track the image `get_skill_node` parsed, call it after the skill is used
+pub fn track_skill_image(root: &WzNodeArc, skill_id: &str, images: &ImageTracker) {
+    if let Ok(skill_node) = get_skill_node(root, skill_id) {
+        images.track_parsed(&skill_node);
+    }
+}
+
+pub fn encode_png(image: &DynamicImage) -> Result<Vec<u8>> {
+    let mut buf = Cursor::new(Vec::new());
+    image
+        .write_to(&mut buf, ImageFormat::Png)
+        .map_err(|e| Error::ImageProcessingError(e.to_string()))?;
+
+    Ok(buf.into_inner())
+}
+
+pub fn get_sound_extension(buffer: &[u8]) -> &'static str {
+    if buffer.starts_with(b"RIFF") {
+        "wav"
+    } else if buffer.starts_with(b"OggS") {
+        "ogg"
+    } else {
+        "mp3"
+    }
+}
+
+fn collect_sounds(node: &WzNodeArc, prefix: &str, result: &mut Vec<(String, WzNodeArc)>) {
+    let node_read = node.read().unwrap();
+
+    for (name, child) in node_read.children.iter() {
+        let name = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}_{}", prefix, name)
+        };
+        let is_sound = {
+            let child_read = child.read().unwrap();
+            child_read.try_as_sound().is_some() || child_read.at(DUMP_SOUND_KEY).is_some()
+        };
+        if is_sound {
+            result.push((name, child.clone()));
+        } else {
+            collect_sounds(child, &name, result);
+        }
+    }
+}
+
+fn new_manifest(id: &str, name: String, node: &WzNodeArc) -> SkillManifest {
+    SkillManifest {
+        id: id.to_string(),
+        name,
+        path: node.read().unwrap().get_full_path(),
+        icons: Vec::new(),
+        animations: Vec::new(),
+        sounds: Vec::new(),
+        skipped: Vec::new(),
+    }
+}
+
+/// collect icons and every animation under the node
+fn collect_node_assets(
+    root: &WzNodeArc,
+    node: &WzNodeArc,
+    manifest: &mut SkillManifest,
+    files: &mut Vec<(String, Vec<u8>)>,
+) -> Result<()> {
+    for icon_name in SKILL_ICON_NAMES {
+        let icon_node = node.read().unwrap().at(icon_name);
+        if let Some(image) = icon_node.and_then(|node| resolve_png(&node, Some(root)).ok()) {
+            let file = format!("{}.png", icon_name);
+            files.push((file.clone(), encode_png(&image)?));
+            manifest.icons.push(file);
+        }
+    }
+
+    for (anim_name, anim_node) in find_animation_nodes(node) {
+        let frames = match resolve_animation_frames(&anim_node, Some(root)) {
+            Ok(frames) => frames,
+            Err(_) => {
+                manifest.skipped.push(anim_name);
+                continue;
+            }
+        };
+        let dir_name = anim_name.replace('/', "_");
+
+        let encoded = frames
+            .par_iter()
+            .map(|frame| encode_png(&frame.image))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut frame_manifests = Vec::with_capacity(frames.len());
+
+        for (frame, data) in frames.into_iter().zip(encoded) {
+            let file = format!("{}/{}.png", dir_name, frame.meta.index);
+            files.push((file.clone(), data));
+            frame_manifests.push(FrameManifest {
+                file,
+                meta: frame.meta,
+            });
+        }
+
+        let anim_read = anim_node.read().unwrap();
+
+        manifest.animations.push(AnimationManifest {
+            name: anim_name,
+            path: anim_read.get_full_path(),
+            z: get_int_at(&anim_read, "z"),
+            frames: frame_manifests,
+        });
+    }
+
+    Ok(())
+}
+
+fn collect_sound_assets(
+    sound_node: &WzNodeArc,
+    manifest: &mut SkillManifest,
+    files: &mut Vec<(String, Vec<u8>)>,
+) {
+    let mut sounds = Vec::new();
+    collect_sounds(sound_node, "", &mut sounds);
+    sounds.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (sound_name, node) in sounds {
+        let node_read = node.read().unwrap();
+        let Ok(buffer) = resolve_sound_buffer(&node_read) else {
+            continue;
+        };
+        let file = format!("sounds/{}.{}", sound_name, get_sound_extension(&buffer));
+        files.push((file.clone(), buffer));
+        manifest.sounds.push(SoundManifest {
+            name: sound_name,
+            file,
+            path: node_read.get_full_path(),
+        });
+    }
+}
+
+fn finish_bundle(
+    manifest: SkillManifest,
+    mut files: Vec<(String, Vec<u8>)>,
+) -> Result<SkillBundle> {
+    files.push((
+        MANIFEST_FILE_NAME.to_string(),
+        serde_json::to_vec_pretty(&manifest)?,
+    ));
+
+    Ok(SkillBundle { manifest, files })
+}
+
+pub fn resolve_skill_bundle(root: &WzNodeArc, skill_id: &str) -> Result<SkillBundle> {
+    let skill_node = get_skill_node(root, skill_id)?;
+
+    let name = root
+        .read()
+        .unwrap()
+        .at_path_parsed(&format!("{}/{}/name", SKILL_STRING_PATH, skill_id))
+        .ok()
+        .and_then(|node| resolve_string_from_node(&node).ok())
+        .unwrap_or(String::from("null"));
+
+    let mut manifest = new_manifest(skill_id, name, &skill_node);
+    let mut files = Vec::new();
+
+    collect_node_assets(root, &skill_node, &mut manifest, &mut files)?;
+
+    let sound_node = root
+        .read()
+        .unwrap()
+        .at_path_parsed(&format!("{}/{}", SKILL_SOUND_PATH, skill_id))
+        .ok();
+
+    if let Some(sound_node) = sound_node {
+        collect_sound_assets(&sound_node, &mut manifest, &mut files);
+    }
+
+    finish_bundle(manifest, files)
+}
+
+/// track the images `resolve_skill_bundle` parsed, the skill, its string and sound
+pub fn track_skill_bundle_images(root: &WzNodeArc, skill_id: &str, images: &ImageTracker) {
+    track_skill_image(root, skill_id, images);
+    images.touch(&format!("{}/{}", SKILL_STRING_PATH, skill_id));
+    images.touch(&format!("{}/{}", SKILL_SOUND_PATH, skill_id));
+}
+
+/// like 1932016 -> Character/TamingMob/01932016.img
+pub fn get_mount_image_path(mount_id: &str) -> String {
+    format!("{}/{:0>8}.img", MOUNT_PATH, mount_id)
+}
+
+/// mount use the same bundle layout as skill, the animations are the actions of the mount
+pub fn resolve_mount_bundle(root: &WzNodeArc, mount_id: &str, name: &str) -> Result<SkillBundle> {
+    let mount_node = root
+        .read()
+        .unwrap()
+        .at_path_parsed(&get_mount_image_path(mount_id))?;
+
+    let mut manifest = new_manifest(mount_id, name.to_string(), &mount_node);
+    let mut files = Vec::new();
+
+    collect_node_assets(root, &mount_node, &mut manifest, &mut files)?;
+
+    finish_bundle(manifest, files)
+}
+
+/// track the image `resolve_mount_bundle` parsed
+pub fn track_mount_bundle_images(root: &WzNodeArc, mount_id: &str, images: &ImageTracker) {
+    let mount_node = root
+        .read()
+        .unwrap()
+        .at_path(&get_mount_image_path(mount_id));
+    if let Some(mount_node) = mount_node {
+        images.track(&mount_node);
+    }
+}
+
+pub fn write_skill_bundle_zip(bundle: &SkillBundle) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for (file, data) in bundle.files.iter() {
+        zip.start_file(file.as_str(), options)?;
+        zip.write_all(data)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+pub fn write_skill_bundle_dir(bundle: &SkillBundle, dir: &Path) -> Result<()> {
+    for (file, data) in bundle.files.iter() {
+        let file_path = dir.join(file);
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file_path, data)?;
+    }
+
+    Ok(())
+}
+
+/// write the bundle into `output`, as `<id>.zip` or a `<id>` folder
+pub fn save_skill_bundle(bundle: &SkillBundle, output: &Path, as_folder: bool) -> Result<PathBuf> {
+    if as_folder {
+        let dir = output.join(&bundle.manifest.id);
+        write_skill_bundle_dir(bundle, &dir)?;
+        Ok(dir)
+    } else {
+        let file = output.join(format!("{}.zip", bundle.manifest.id));
+        std::fs::create_dir_all(output)?;
+        std::fs::write(&file, write_skill_bundle_zip(bundle)?)?;
+        Ok(file)
+    }
+}
@@ -0,0 +1,454 @@
+use image::{imageops, RgbaImage};
+use rayon::prelude::*;
+use serde::Serialize;
+use wz_reader::{WzNode, WzNodeArc};
+
+use super::anim_encode::ComposedAnimation;
+use super::animation::{
+    get_frame_nodes, get_int_at, get_vector_at, is_frame_node, resolve_animation_frames,
+    resolve_uol_node, Frame, DEFAULT_FRAME_DELAY,
+};
+use super::skill_export::get_skill_node;
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LayerKind {
+    Effect,
+    Special,
+    Ball,
+    Hit,
+    Mob,
+    Affected,
+}
+
+#[derive(Default)]
+pub struct TimelineOptions {
+    /// when the hit and mob layers start, default to the end of the ball or 0 without ball
+    pub hit_delay: Option<i32>,
+    /// where the target stands relative to the character, the hit and mob layers are drawn there
+    pub target_offset: (i32, i32),
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineFrame {
+    pub delay: i32,
+    pub origin: (i32, i32),
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineLayer {
+    /// the path relative to the skill node, like `effect0` or `hit/0`
+    pub name: String,
+    pub path: String,
+    pub kind: LayerKind,
+    /// in milliseconds from the start of the skill
+    pub start: i32,
+    /// one pass of the frames
+    pub duration: i32,
+    /// loop until the skill ends
+    pub repeat: bool,
+    pub z: i32,
+    /// negative z is drawn behind the character
+    pub front: bool,
+    /// where the layer origin is relative to the character
+    pub offset: (i32, i32),
+    pub frames: Vec<TimelineFrame>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillTimeline {
+    pub skill_id: String,
+    pub path: String,
+    /// ordered from the bottom to the top
+    pub layers: Vec<TimelineLayer>,
+    pub total_duration: i32,
+    /// the resolved node of each layer, same order as `layers`
+    #[serde(skip)]
+    nodes: Vec<WzNodeArc>,
+}
+
+fn get_layer_kind(name: &str) -> Option<LayerKind> {
+    let is_numbered = |prefix: &str| {
+        name.strip_prefix(prefix)
+            .map_or(false, |rest| rest.chars().all(|c| c.is_ascii_digit()))
+    };
+
+    if is_numbered("effect") {
+        Some(LayerKind::Effect)
+    } else if is_numbered("special") {
+        Some(LayerKind::Special)
+    } else if is_numbered("ball") {
+        Some(LayerKind::Ball)
+    } else if is_numbered("affected") {
+        Some(LayerKind::Affected)
+    } else if is_numbered("mob") {
+        Some(LayerKind::Mob)
+    } else if name == "hit" {
+        Some(LayerKind::Hit)
+    } else {
+        None
+    }
+}
+
+/// the first child is a frame, a folder like `hit` with one animation per target is not
+fn is_animation_node(node: &WzNode) -> bool {
+    node.at("0")
+        .map_or(false, |first| is_frame_node(&first.read().unwrap()))
+}
+
+/// (name, node) of every layer under the skill, the `hit` folder use its first animation
+fn collect_layer_nodes(
+    skill_node: &WzNodeArc,
+    root: &WzNodeArc,
+) -> Vec<(String, LayerKind, WzNodeArc)> {
+    let mut children = skill_node
+        .read()
+        .unwrap()
+        .children
+        .iter()
+        .filter_map(|(name, child)| Some((name.to_string(), get_layer_kind(name)?, child.clone())))
+        .collect::<Vec<_>>();
+
+    children.sort_by(|a, b| a.0.cmp(&b.0));
+
+    children
+        .into_iter()
+        .filter_map(|(name, kind, node)| {
+            let node = resolve_uol_node(&node, Some(root))?;
+
+            let is_animation = is_animation_node(&node.read().unwrap());
+            if is_animation {
+                return Some((name, kind, node));
+            }
+
+            // like hit/0, the folder has one animation per target
+            let first = node.read().unwrap().at("0")?;
+            let first = resolve_uol_node(&first, Some(root))?;
+            let is_animation = is_animation_node(&first.read().unwrap());
+            is_animation.then(|| (format!("{}/0", name), kind, first))
+        })
+        .collect()
+}
+
+fn resolve_timeline_frames(layer_node: &WzNodeArc, root: &WzNodeArc) -> Vec<TimelineFrame> {
+    get_frame_nodes(layer_node)
+        .iter()
+        .map(|frame_node| {
+            let frame_node = resolve_uol_node(frame_node, Some(root)).unwrap_or(frame_node.clone());
+            let frame_read = frame_node.read().unwrap();
+            TimelineFrame {
+                delay: get_int_at(&frame_read, "delay").unwrap_or(DEFAULT_FRAME_DELAY),
+                origin: get_vector_at(&frame_read, "origin").unwrap_or((0, 0)),
+            }
+        })
+        .collect()
+}
+
+/// build the playback plan of every effect layer of the skill
+pub fn resolve_skill_timeline(
+    root: &WzNodeArc,
+    skill_id: &str,
+    options: &TimelineOptions,
+) -> Result<SkillTimeline> {
+    let skill_node = get_skill_node(root, skill_id)?;
+    let skill_path = skill_node.read().unwrap().get_full_path();
+
+    let mut layers = collect_layer_nodes(&skill_node, root)
+        .into_iter()
+        .filter_map(|(name, kind, node)| {
+            let frames = resolve_timeline_frames(&node, root);
+            if frames.is_empty() {
+                return None;
+            }
+
+            let node_read = node.read().unwrap();
+            let z = get_int_at(&node_read, "z")
+                .or_else(|| {
+                    node_read
+                        .at("0")
+                        .and_then(|f| get_int_at(&f.read().unwrap(), "z"))
+                })
+                .unwrap_or(0);
+            let duration = frames.iter().map(|f| f.delay.max(0)).sum::<i32>();
+
+            let layer = TimelineLayer {
+                path: node_read.get_full_path(),
+                name,
+                kind,
+                start: 0,
+                duration,
+                // a loop without duration never ends
+                repeat: get_int_at(&node_read, "repeat").unwrap_or(0) != 0 && duration > 0,
+                z,
+                front: z >= 0,
+                offset: (0, 0),
+                frames,
+            };
+            drop(node_read);
+
+            Some((layer, node))
+        })
+        .collect::<Vec<_>>();
+
+    let ball_duration = layers
+        .iter()
+        .map(|(layer, _)| layer)
+        .filter(|layer| layer.kind == LayerKind::Ball)
+        .map(|layer| layer.duration)
+        .max();
+    let hit_start = options.hit_delay.or(ball_duration).unwrap_or(0);
+
+    // the buff shows on the character after the casting
+    let cast_duration = layers
+        .iter()
+        .map(|(layer, _)| layer)
+        .filter(|layer| matches!(layer.kind, LayerKind::Effect | LayerKind::Special))
+        .filter(|layer| !layer.repeat)
+        .map(|layer| layer.duration)
+        .max()
+        .unwrap_or(0);
+
+    for (layer, _) in layers.iter_mut() {
+        match layer.kind {
+            LayerKind::Hit | LayerKind::Mob => {
+                layer.start = hit_start;
+                layer.offset = options.target_offset;
+            }
+            LayerKind::Affected => layer.start = cast_duration,
+            _ => {}
+        }
+    }
+
+    let once_end = layers
+        .iter()
+        .map(|(layer, _)| layer)
+        .filter(|layer| !layer.repeat)
+        .map(|layer| layer.start + layer.duration)
+        .max();
+    // only the looping layers, play one pass of the longest
+    let total_duration = once_end.unwrap_or_else(|| {
+        layers
+            .iter()
+            .map(|(layer, _)| layer.start + layer.duration)
+            .max()
+            .unwrap_or(0)
+    });
+
+    // stable, so the same z keeps the order in wz
+    layers.sort_by_key(|(layer, _)| layer.z);
+    let (layers, nodes) = layers.into_iter().unzip();
+
+    Ok(SkillTimeline {
+        skill_id: skill_id.to_string(),
+        path: skill_path,
+        layers,
+        total_duration,
+        nodes,
+    })
+}
+
+/// the frame index of the layer at the time, None if the layer is not showing
+fn get_active_frame(layer: &TimelineLayer, time: i32) -> Option<usize> {
+    let mut local = time - layer.start;
+    if local < 0 || layer.duration <= 0 {
+        return None;
+    }
+    if layer.repeat {
+        local %= layer.duration;
+    } else if local >= layer.duration {
+        return None;
+    }
+
+    let mut elapsed = 0;
+    layer.frames.iter().position(|frame| {
+        elapsed += frame.delay.max(0);
+        local < elapsed
+    })
+}
+
+/// every time any layer changes its frame
+fn get_change_points(timeline: &SkillTimeline) -> Vec<i32> {
+    let mut points = vec![0, timeline.total_duration];
+
+    for layer in timeline.layers.iter() {
+        let mut time = layer.start;
+        'pass: loop {
+            for frame in layer.frames.iter() {
+                if time >= timeline.total_duration {
+                    break 'pass;
+                }
+                points.push(time);
+                time += frame.delay.max(0);
+            }
+            if !layer.repeat {
+                points.push(time.min(timeline.total_duration));
+                break;
+            }
+        }
+    }
+
+    points.sort_unstable();
+    points.dedup();
+    points
+}
+
+/// (left, top, right, bottom) of the frame relative to the character origin
+fn get_frame_rect(layer: &TimelineLayer, frame: &Frame) -> (i32, i32, i32, i32) {
+    let left = layer.offset.0 - frame.meta.origin.0;
+    let top = layer.offset.1 - frame.meta.origin.1;
+    (
+        left,
+        top,
+        left + frame.meta.width as i32,
+        top + frame.meta.height as i32,
+    )
+}
+
+/// decode the layers and compose them into one animation, frames are split where any layer changes
+pub fn render_skill_timeline(
+    root: &WzNodeArc,
+    timeline: &SkillTimeline,
+) -> Result<ComposedAnimation> {
+    let decoded = timeline
+        .nodes
+        .par_iter()
+        .map(|node| resolve_animation_frames(node, Some(root)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let rects = timeline
+        .layers
+        .iter()
+        .zip(decoded.iter())
+        .flat_map(|(layer, frames)| frames.iter().map(move |frame| get_frame_rect(layer, frame)));
+
+    let (left, top, right, bottom) =
+        rects.fold((i32::MAX, i32::MAX, i32::MIN, i32::MIN), |acc, rect| {
+            (
+                acc.0.min(rect.0),
+                acc.1.min(rect.1),
+                acc.2.max(rect.2),
+                acc.3.max(rect.3),
+            )
+        });
+
+    if left >= right || top >= bottom {
+        return Err(Error::ImageProcessingError(
+            "the skill has no effect frame".to_string(),
+        ));
+    }
+
+    let width = (right - left) as u32;
+    let height = (bottom - top) as u32;
+
+    let points = get_change_points(timeline);
+
+    let frames = points
+        .par_windows(2)
+        .map(|window| {
+            let (time, delay) = (window[0], window[1] - window[0]);
+            let mut canvas = RgbaImage::new(width, height);
+
+            for (layer, frames) in timeline.layers.iter().zip(decoded.iter()) {
+                let Some(frame) = get_active_frame(layer, time).and_then(|i| frames.get(i)) else {
+                    continue;
+                };
+                let rect = get_frame_rect(layer, frame);
+                imageops::overlay(
+                    &mut canvas,
+                    &frame.image.to_rgba8(),
+                    (rect.0 - left) as i64,
+                    (rect.1 - top) as i64,
+                );
+            }
+
+            (canvas, delay)
+        })
+        .filter(|(_, delay)| *delay > 0)
+        .collect::<Vec<_>>();
+
+    Ok(ComposedAnimation {
+        width,
+        height,
+        origin: (-left, -top),
+        // the skill loops only when every layer does
+        repeat: timeline.layers.iter().all(|layer| layer.repeat),
+        frames,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use wz_reader::property::{WzSubProperty, WzValue};
+    use wz_reader::WzObjectType;
+
+    use super::*;
+    use crate::utils::DUMP_CANVAS_KEY;
+
+    fn add_folder(parent: &WzNodeArc, name: &str) -> WzNodeArc {
+        let node = WzNode::from_str(
+            name,
+            WzObjectType::Property(WzSubProperty::Property),
+            Some(parent),
+        )
+        .into_lock();
+        insert_child(parent, node.clone());
+        node
+    }
+
+    fn insert_child(parent: &WzNodeArc, node: WzNodeArc) {
+        let name = node.read().unwrap().name.clone();
+        parent.write().unwrap().children.insert(name, node);
+    }
+
+    /// a dump canvas, enough for `is_frame_node`
+    fn add_frames(parent: &WzNodeArc, count: usize) {
+        for i in 0..count {
+            let frame = add_folder(parent, &i.to_string());
+            let data = WzNode::from_str(
+                DUMP_CANVAS_KEY,
+                WzObjectType::Value(WzValue::ParsedString(String::new())),
+                Some(&frame),
+            )
+            .into_lock();
+            insert_child(&frame, data);
+        }
+    }
+
+    fn layer_names(skill: &WzNodeArc, root: &WzNodeArc) -> Vec<String> {
+        collect_layer_nodes(skill, root)
+            .into_iter()
+            .map(|(name, _, _)| name)
+            .collect()
+    }
+
+    #[test]
+    fn collects_hit_frames_and_hit_folders() {
+        let root = WzNode::from_str(
+            "Base",
+            WzObjectType::Property(WzSubProperty::Property),
+            None,
+        )
+        .into_lock();
+
+        // hit/<n>, the frames are right under hit
+        let flat = add_folder(&root, "1001005");
+        add_frames(&add_folder(&flat, "effect"), 2);
+        add_frames(&add_folder(&flat, "hit"), 3);
+        assert_eq!(layer_names(&flat, &root), ["effect", "hit"]);
+
+        // hit/0/<n>, one animation per target
+        let nested = add_folder(&root, "1001006");
+        let hit = add_folder(&nested, "hit");
+        add_frames(&add_folder(&hit, "0"), 3);
+        add_frames(&add_folder(&hit, "1"), 3);
+        assert_eq!(layer_names(&nested, &root), ["hit/0"]);
+
+        let (_, kind, node) = collect_layer_nodes(&nested, &root).remove(0);
+        assert_eq!(kind, LayerKind::Hit);
+        assert_eq!(get_frame_nodes(&node).len(), 3);
+    }
+}
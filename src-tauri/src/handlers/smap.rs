@@ -6,7 +6,9 @@ use crate::Result;
 pub fn get_smap(root: &WzNodeArc) -> Result<WzNodeArc> {
     let node = root.read().unwrap();
 
-    let node = node.at(SMAP_PATH).ok_or(crate::Error::NodeNotFound)?;
+    let node = node
+        .at(SMAP_PATH)
+        .ok_or_else(|| crate::Error::node_not_found(&node, SMAP_PATH))?;
 
     Ok(node)
 }
@@ -147,7 +147,7 @@ pub fn get_equip_string(root: &WzNodeArc) -> Result<WzNodeArc> {
 
     let node = node
         .at_path(EQUIP_STRING_PATH)
-        .ok_or(crate::Error::NodeNotFound)?;
+        .ok_or_else(|| crate::Error::node_not_found(&node, EQUIP_STRING_PATH))?;
 
     Ok(node)
 }
@@ -157,7 +157,7 @@ pub fn get_equip_node(root: &WzNodeArc) -> Result<WzNodeArc> {
 
     let node = node
         .at(CHARACTER_ITEM_PATH)
-        .ok_or(crate::Error::NodeNotFound)?;
+        .ok_or_else(|| crate::Error::node_not_found(&node, CHARACTER_ITEM_PATH))?;
 
     Ok(node)
 }
@@ -361,11 +361,10 @@ pub fn resolve_equip_string(
         category_result.extend(extra_nodes);
 
         if extra_info {
-            let effect_node = root
-                .read()
-                .unwrap()
-                .at_path(EQUIP_EFFECT_PATH)
-                .ok_or(Error::NodeNotFound)?;
+            let effect_node = root.read().unwrap().at_path(EQUIP_EFFECT_PATH);
+            let effect_node = effect_node.ok_or_else(|| {
+                Error::node_not_found(&root.read().unwrap(), EQUIP_EFFECT_PATH)
+            })?;
 
             category_result.par_iter_mut().for_each(|item| {
                 let item_node = get_item_node_from_category(&category_equip_node, &item.1);
@@ -1,31 +1,446 @@
-use webp_animation::{Encoder, EncoderOptions, Error, WebPData};
-
-// the data layout [width:u32, height:u32, frame_count:u32, (dealy:u32, frame_len:u32, frame_lens of data)*]
-
-pub fn encode_wep_animation(data: &[u8]) -> Result<WebPData, Error> {
-    let mut offset = 0;
-    let width = pull_u32(data, &mut offset);
-    let height = pull_u32(data, &mut offset);
-    let frame_count = pull_u32(data, &mut offset);
-    let mut ms = 0;
-    let mut options = EncoderOptions::default();
-    options.anim_params.loop_count = 0;
-    let mut encoder = Encoder::new_with_options((width, height), options)?;
-    for _ in 0..frame_count {
-        let delay = pull_u32(data, &mut offset) as i32;
-        let len = pull_u32(data, &mut offset) as usize;
-        encoder.add_frame(&data[offset..offset + len], ms)?;
-
-        offset += len;
-        ms += delay;
-    }
-    encoder.finalize(ms)
-}
-
-#[inline]
-fn pull_u32(data: &[u8], offset: &mut usize) -> u32 {
-    let mut bytes = [0; 4];
-    bytes.copy_from_slice(&data[*offset..*offset + 4]);
-    *offset += 4;
-    u32::from_le_bytes(bytes)
+use std::io::Cursor;
+
+use image::codecs::png::PngDecoder;
+use image::{imageops, DynamicImage, ImageDecoder, RgbaImage};
+use webp_animation::{Encoder, EncoderOptions, EncodingConfig, EncodingType, LossyEncodingConfig};
+
+use crate::{Error, Result};
+
+// the frame container, every number is little endian
+// header: [magic:"WZAF", version:u8, flags:u8, quality:u8, method:u8, width:u32, height:u32,
+//          loop_count:u32, keyframe_interval:u32, frame_count:u32]
+// table:  frame_count * [offset:u32, length:u32, format:u8, reserved:[u8;3],
+//          x:u32, y:u32, width:u32, height:u32, delay:u32]
+// the payload of a frame is data[offset..offset + length], raw rgba or a png file.
+// a frame smaller than the canvas is drawn at (x, y) on a clear canvas
+//
+// flags bit 0 is lossless, quality is 0 ~ 100, method is 0 ~ 6,
+// loop_count 0 loops forever and keyframe_interval 0 keeps the encoder default
+
+pub const FRAME_CONTAINER_MAGIC: &[u8; 4] = b"WZAF";
+pub const FRAME_CONTAINER_VERSION: u8 = 1;
+
+const HEADER_LEN: usize = 28;
+const FRAME_ENTRY_LEN: usize = 32;
+const FLAG_LOSSLESS: u8 = 1;
+/// the largest canvas webp can store
+const MAX_DIMENSION: u32 = 16383;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramePayload {
+    Rgba,
+    Png,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebPOptions {
+    pub lossless: bool,
+    /// 0 ~ 100, the compression effort when lossless
+    pub quality: u8,
+    /// 0 ~ 6, higher is slower and smaller
+    pub method: u8,
+    /// 0 loops forever
+    pub loop_count: u32,
+    /// the max distance between key frames, 0 keeps the encoder default
+    pub keyframe_interval: u32,
+}
+
+impl WebPOptions {
+    fn validate(&self) -> Result<()> {
+        if self.quality > 100 {
+            return Err(invalid(format!("quality {} is over 100", self.quality)));
+        }
+        if self.method > 6 {
+            return Err(invalid(format!("method {} is over 6", self.method)));
+        }
+        Ok(())
+    }
+
+    pub fn get_encoder_options(&self) -> EncoderOptions {
+        let mut options = EncoderOptions::default();
+        options.anim_params.loop_count = self.loop_count as i32;
+        options.encoding_config = Some(EncodingConfig {
+            encoding_type: if self.lossless {
+                EncodingType::Lossless
+            } else {
+                EncodingType::Lossy(LossyEncodingConfig::default())
+            },
+            quality: self.quality as f32,
+            method: self.method as usize,
+        });
+
+        if self.keyframe_interval > 0 {
+            // libwebp wants kmin < kmax
+            options.kmax = self.keyframe_interval as isize;
+            options.kmin = (self.keyframe_interval / 2) as isize;
+        }
+
+        options
+    }
+}
+
+/// a frame of the container, `data` is borrowed from the input
+pub struct ContainerFrame<'a> {
+    pub payload: FramePayload,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub delay: u32,
+    pub data: &'a [u8],
+}
+
+pub struct FrameContainer<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub options: WebPOptions,
+    pub frames: Vec<ContainerFrame<'a>>,
+}
+
+fn invalid(message: String) -> Error {
+    Error::InvalidFrameContainer(message)
+}
+
+/// read the numbers in order, every read checks the bounds first
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], offset: usize) -> Self {
+        Reader { data, offset }
+    }
+
+    fn take(&mut self, len: usize, field: &str) -> Result<&'a [u8]> {
+        let bytes = self
+            .offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.offset..end))
+            .ok_or_else(|| {
+                invalid(format!(
+                    "truncated at {}, need {} bytes at offset {} but the data is {} bytes",
+                    field,
+                    len,
+                    self.offset,
+                    self.data.len()
+                ))
+            })?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self, field: &str) -> Result<u8> {
+        Ok(self.take(1, field)?[0])
+    }
+
+    fn read_u32(&mut self, field: &str) -> Result<u32> {
+        let bytes = self.take(4, field)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+fn parse_frame<'a>(data: &'a [u8], index: usize, canvas: (u32, u32)) -> Result<ContainerFrame<'a>> {
+    let mut reader = Reader::new(data, HEADER_LEN + index * FRAME_ENTRY_LEN);
+    let field = |name: &str| format!("frame {} {}", index, name);
+
+    let offset = reader.read_u32(&field("offset"))? as usize;
+    let length = reader.read_u32(&field("length"))? as usize;
+    let payload = match reader.read_u8(&field("format"))? {
+        0 => FramePayload::Rgba,
+        1 => FramePayload::Png,
+        other => {
+            return Err(invalid(format!(
+                "frame {} has unknown format {}, expected 0 (rgba) or 1 (png)",
+                index, other
+            )))
+        }
+    };
+    reader.take(3, &field("reserved"))?;
+    let x = reader.read_u32(&field("x"))?;
+    let y = reader.read_u32(&field("y"))?;
+    let width = reader.read_u32(&field("width"))?;
+    let height = reader.read_u32(&field("height"))?;
+    let delay = reader.read_u32(&field("delay"))?;
+
+    if width == 0 || height == 0 {
+        return Err(invalid(format!("frame {} is empty", index)));
+    }
+    let fits = |start: u32, size: u32, limit: u32| {
+        start.checked_add(size).map_or(false, |end| end <= limit)
+    };
+    if !fits(x, width, canvas.0) || !fits(y, height, canvas.1) {
+        return Err(invalid(format!(
+            "frame {} at ({}, {}) with size {}x{} is outside the {}x{} canvas",
+            index, x, y, width, height, canvas.0, canvas.1
+        )));
+    }
+
+    let data = Reader::new(data, offset).take(length, &field("payload"))?;
+
+    if payload == FramePayload::Rgba {
+        let expected = width as usize * height as usize * 4;
+        if data.len() != expected {
+            return Err(invalid(format!(
+                "frame {} has {} bytes of rgba, expected {} for {}x{}",
+                index,
+                data.len(),
+                expected,
+                width,
+                height
+            )));
+        }
+    }
+
+    Ok(ContainerFrame {
+        payload,
+        x,
+        y,
+        width,
+        height,
+        delay,
+        data,
+    })
+}
+
+/// validate the header and the frame table, the payloads are decoded when encoding
+pub fn parse_frame_container(data: &[u8]) -> Result<FrameContainer<'_>> {
+    let mut reader = Reader::new(data, 0);
+
+    let magic = reader.take(4, "magic")?;
+    if magic != FRAME_CONTAINER_MAGIC {
+        return Err(invalid(format!(
+            "bad magic {:?}, expected {:?}",
+            String::from_utf8_lossy(magic),
+            String::from_utf8_lossy(FRAME_CONTAINER_MAGIC)
+        )));
+    }
+
+    let version = reader.read_u8("version")?;
+    if version != FRAME_CONTAINER_VERSION {
+        return Err(invalid(format!(
+            "unsupported version {}, expected {}",
+            version, FRAME_CONTAINER_VERSION
+        )));
+    }
+
+    let flags = reader.read_u8("flags")?;
+    let quality = reader.read_u8("quality")?;
+    let method = reader.read_u8("method")?;
+    let width = reader.read_u32("width")?;
+    let height = reader.read_u32("height")?;
+    let loop_count = reader.read_u32("loop_count")?;
+    let keyframe_interval = reader.read_u32("keyframe_interval")?;
+    let frame_count = reader.read_u32("frame_count")? as usize;
+
+    if width == 0 || height == 0 || width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return Err(invalid(format!(
+            "canvas {}x{} is out of 1 ~ {}",
+            width, height, MAX_DIMENSION
+        )));
+    }
+    if frame_count == 0 {
+        return Err(invalid("no frame".to_string()));
+    }
+    // the table must fit before allocating anything for it
+    let table_len = frame_count
+        .checked_mul(FRAME_ENTRY_LEN)
+        .and_then(|len| len.checked_add(HEADER_LEN));
+    if table_len.map_or(true, |len| len > data.len()) {
+        return Err(invalid(format!(
+            "{} frames don't fit in {} bytes",
+            frame_count,
+            data.len()
+        )));
+    }
+
+    let options = WebPOptions {
+        lossless: flags & FLAG_LOSSLESS != 0,
+        quality,
+        method,
+        loop_count,
+        keyframe_interval,
+    };
+    options.validate()?;
+
+    let frames = (0..frame_count)
+        .map(|index| parse_frame(data, index, (width, height)))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(FrameContainer {
+        width,
+        height,
+        options,
+        frames,
+    })
+}
+
+fn decode_frame(frame: &ContainerFrame, index: usize) -> Result<RgbaImage> {
+    let image = match frame.payload {
+        FramePayload::Rgba => RgbaImage::from_raw(frame.width, frame.height, frame.data.to_vec()),
+        FramePayload::Png => {
+            let decoder = PngDecoder::new(Cursor::new(frame.data))
+                .map_err(|e| invalid(format!("frame {} is not a valid png: {}", index, e)))?;
+            // check the header before decoding, a small png can claim a huge size
+            let (width, height) = decoder.dimensions();
+            if (width, height) != (frame.width, frame.height) {
+                return Err(invalid(format!(
+                    "frame {} png is {}x{}, but the table says {}x{}",
+                    index, width, height, frame.width, frame.height
+                )));
+            }
+            let image = DynamicImage::from_decoder(decoder)
+                .map_err(|e| invalid(format!("frame {} is not a valid png: {}", index, e)))?;
+            Some(image.to_rgba8())
+        }
+    };
+
+    image.ok_or_else(|| invalid(format!("frame {} has bad rgba data", index)))
+}
+
+/// encode the frames of the container as an animated webp
+pub fn encode_frame_container(container: &FrameContainer) -> Result<Vec<u8>> {
+    let canvas_size = (container.width, container.height);
+    let mut encoder =
+        Encoder::new_with_options(canvas_size, container.options.get_encoder_options())
+            .map_err(|e| Error::ImageProcessingError(format!("Encoder init failed: {}", e)))?;
+
+    let mut timestamp: i32 = 0;
+    for (index, frame) in container.frames.iter().enumerate() {
+        let image = decode_frame(frame, index)?;
+
+        let canvas = if (frame.width, frame.height) == canvas_size {
+            image
+        } else {
+            let mut canvas = RgbaImage::new(container.width, container.height);
+            imageops::replace(&mut canvas, &image, frame.x as i64, frame.y as i64);
+            canvas
+        };
+
+        encoder
+            .add_frame(canvas.as_raw(), timestamp)
+            .map_err(|e| Error::ImageProcessingError(format!("Add frame failed: {}", e)))?;
+
+        timestamp = timestamp.saturating_add(frame.delay.min(i32::MAX as u32) as i32);
+    }
+
+    let data = encoder
+        .finalize(timestamp)
+        .map_err(|e| Error::ImageProcessingError(format!("Finalize failed: {}", e)))?;
+
+    Ok(data.to_vec())
+}
+
+pub fn encode_webp_container(data: &[u8]) -> Result<Vec<u8>> {
+    let container = parse_frame_container(data)?;
+    encode_frame_container(&container)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(width: u32, height: u32, frame_count: u32) -> Vec<u8> {
+        let mut data = FRAME_CONTAINER_MAGIC.to_vec();
+        data.extend([FRAME_CONTAINER_VERSION, 0, 75, 4]);
+        for value in [width, height, 0, 0, frame_count] {
+            data.extend(value.to_le_bytes());
+        }
+        data
+    }
+
+    fn push_rgba_frame(data: &mut Vec<u8>, offset: u32, rect: (u32, u32, u32, u32)) {
+        let (x, y, width, height) = rect;
+        data.extend(offset.to_le_bytes());
+        data.extend((width * height * 4).to_le_bytes());
+        data.extend([0, 0, 0, 0]);
+        for value in [x, y, width, height, 100] {
+            data.extend(value.to_le_bytes());
+        }
+    }
+
+    /// a container with a single rgba frame at `rect` on a 4x4 canvas
+    fn single_frame(rect: (u32, u32, u32, u32)) -> Vec<u8> {
+        let mut data = header(4, 4, 1);
+        let offset = (HEADER_LEN + FRAME_ENTRY_LEN) as u32;
+        push_rgba_frame(&mut data, offset, rect);
+        data.resize(data.len() + (rect.2 * rect.3 * 4) as usize, 0xff);
+        data
+    }
+
+    fn is_invalid(data: &[u8]) -> bool {
+        matches!(
+            parse_frame_container(data),
+            Err(Error::InvalidFrameContainer(_))
+        )
+    }
+
+    #[test]
+    fn parses_a_valid_container() {
+        let data = single_frame((1, 1, 2, 2));
+        let container = parse_frame_container(&data).unwrap();
+        assert_eq!((container.width, container.height), (4, 4));
+        assert_eq!(container.frames.len(), 1);
+        let frame = &container.frames[0];
+        assert_eq!((frame.x, frame.y, frame.width, frame.height), (1, 1, 2, 2));
+        assert_eq!(frame.data.len(), 16);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let data = single_frame((0, 0, 4, 4));
+        for len in [0, 3, HEADER_LEN - 1, HEADER_LEN + 10, data.len() - 1] {
+            assert!(is_invalid(&data[..len]), "accepted {} bytes", len);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut data = single_frame((0, 0, 4, 4));
+        data[..4].copy_from_slice(b"RIFF");
+        assert!(is_invalid(&data));
+    }
+
+    #[test]
+    fn rejects_bad_version() {
+        let mut data = single_frame((0, 0, 4, 4));
+        data[4] = FRAME_CONTAINER_VERSION + 1;
+        assert!(is_invalid(&data));
+    }
+
+    #[test]
+    fn rejects_frames_outside_the_canvas() {
+        assert!(is_invalid(&single_frame((3, 0, 2, 2))));
+        assert!(is_invalid(&single_frame((0, 0, 4, 5))));
+        assert!(is_invalid(&single_frame((u32::MAX, 0, 1, 1))));
+    }
+
+    #[test]
+    fn rejects_png_of_another_size() {
+        let mut png = Cursor::new(Vec::new());
+        RgbaImage::new(64, 64)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let png = png.into_inner();
+
+        let mut data = header(4, 4, 1);
+        let offset = (HEADER_LEN + FRAME_ENTRY_LEN) as u32;
+        push_rgba_frame(&mut data, offset, (0, 0, 4, 4));
+        data[HEADER_LEN + 4..HEADER_LEN + 8].copy_from_slice(&(png.len() as u32).to_le_bytes());
+        data[HEADER_LEN + 8] = 1;
+        data.extend(&png);
+
+        let container = parse_frame_container(&data).unwrap();
+        assert!(matches!(
+            decode_frame(&container.frames[0], 0),
+            Err(Error::InvalidFrameContainer(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_overflowing_frame_count() {
+        let mut data = single_frame((0, 0, 1, 1));
+        data[24..28].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(is_invalid(&data));
+    }
 }
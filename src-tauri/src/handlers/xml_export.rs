@@ -0,0 +1,319 @@
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use quick_xml::events::{BytesDecl, BytesStart, Event};
+use quick_xml::Writer;
+use wz_reader::{
+    property::{WzSubProperty, WzValue},
+    util::node_util,
+    WzNode, WzNodeArc, WzNodeCast, WzObjectType,
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use super::audio::resolve_sound_buffer;
+use super::node_info::get_natural_sorted_children;
+use super::png::resolve_png;
+use super::skill_export::{encode_png, get_sound_extension};
+use crate::utils::{DUMP_CANVAS_KEY, DUMP_SOUND_KEY};
+use crate::Result;
+
+type XmlWriter = Writer<Vec<u8>>;
+
+pub struct XmlExport {
+    /// the exported node name, like `100.img`
+    pub name: String,
+    pub xml: Vec<u8>,
+    /// (path relative to the xml, file content) of canvases and sounds when not inline
+    pub files: Vec<(String, Vec<u8>)>,
+}
+
+struct XmlExportContext<'a> {
+    root: Option<&'a WzNodeArc>,
+    inline_media: bool,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+/// keep a node name safe as a file name and in `Content-Disposition`, like `a:b` -> `a_b`
+fn sanitize_file_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    // an empty name, `.` or `..` would point out of the folder
+    if name.trim_matches('.').is_empty() {
+        "_".repeat(name.len().max(1))
+    } else {
+        name
+    }
+}
+
+/// the path of a side file, every node name in the path is sanitized
+fn get_side_file_path(relative_path: &str, extension: &str) -> String {
+    let path = relative_path
+        .split('/')
+        .map(sanitize_file_name)
+        .collect::<Vec<_>>()
+        .join("/");
+
+    format!("{}.{}", path, extension)
+}
+
+fn get_sorted_children(node: &WzNode) -> Vec<(String, WzNodeArc)> {
+    get_natural_sorted_children(node)
+        .into_iter()
+        .filter(|(name, _)| name != DUMP_CANVAS_KEY && name != DUMP_SOUND_KEY)
+        .collect()
+}
+
+fn is_canvas(node: &WzNode) -> bool {
+    matches!(
+        node.object_type,
+        WzObjectType::Property(WzSubProperty::PNG(_))
+    ) || node.at(DUMP_CANVAS_KEY).is_some()
+}
+
+fn is_sound(node: &WzNode) -> bool {
+    node.try_as_sound().is_some() || node.at(DUMP_SOUND_KEY).is_some()
+}
+
+/// the value element of a leaf node, None if the node is a container
+fn get_value_element<'a>(name: &'a str, node: &WzNode) -> Option<BytesStart<'a>> {
+    let WzObjectType::Value(value) = &node.object_type else {
+        return None;
+    };
+
+    let element = match value {
+        WzValue::Null => BytesStart::new("null").with_attributes([("name", name)]),
+        WzValue::Short(v) => BytesStart::new("short")
+            .with_attributes([("name", name), ("value", v.to_string().as_str())]),
+        WzValue::Int(v) => BytesStart::new("int")
+            .with_attributes([("name", name), ("value", v.to_string().as_str())]),
+        WzValue::Long(v) => BytesStart::new("long")
+            .with_attributes([("name", name), ("value", v.to_string().as_str())]),
+        WzValue::Float(v) => BytesStart::new("float")
+            .with_attributes([("name", name), ("value", v.to_string().as_str())]),
+        WzValue::Double(v) => BytesStart::new("double")
+            .with_attributes([("name", name), ("value", v.to_string().as_str())]),
+        WzValue::Vector(v) => BytesStart::new("vector").with_attributes([
+            ("name", name),
+            ("x", v.0.to_string().as_str()),
+            ("y", v.1.to_string().as_str()),
+        ]),
+        WzValue::ParsedString(v) => {
+            BytesStart::new("string").with_attributes([("name", name), ("value", v.as_str())])
+        }
+        _ => {
+            if let Some(uol) = node.try_as_uol() {
+                let value = uol.get_string().unwrap_or_default();
+                BytesStart::new("uol").with_attributes([("name", name), ("value", value.as_str())])
+            } else if let Some(string) = node.try_as_string() {
+                let value = string.get_string().unwrap_or_default();
+                BytesStart::new("string")
+                    .with_attributes([("name", name), ("value", value.as_str())])
+            } else {
+                BytesStart::new("null").with_attributes([("name", name)])
+            }
+        }
+    };
+
+    Some(element)
+}
+
+fn get_canvas_element<'a>(
+    name: &'a str,
+    node: &WzNodeArc,
+    relative_path: &str,
+    context: &mut XmlExportContext,
+) -> Result<BytesStart<'a>> {
+    let image = resolve_png(node, context.root)?;
+    let data = encode_png(&image)?;
+
+    let mut element = BytesStart::new("canvas").with_attributes([
+        ("name", name),
+        ("width", image.width().to_string().as_str()),
+        ("height", image.height().to_string().as_str()),
+    ]);
+
+    if context.inline_media {
+        element.push_attribute(("basedata", STANDARD.encode(&data).as_str()));
+    } else {
+        let file = get_side_file_path(relative_path, "png");
+        element.push_attribute(("src", file.as_str()));
+        context.files.push((file, data));
+    }
+
+    Ok(element)
+}
+
+fn get_sound_element<'a>(
+    name: &'a str,
+    node: &WzNode,
+    relative_path: &str,
+    context: &mut XmlExportContext,
+) -> Result<BytesStart<'a>> {
+    let data = resolve_sound_buffer(node)?;
+
+    let mut element = BytesStart::new("sound").with_attributes([("name", name)]);
+
+    if context.inline_media {
+        element.push_attribute(("basedata", STANDARD.encode(&data).as_str()));
+    } else {
+        let file = get_side_file_path(relative_path, get_sound_extension(&data));
+        element.push_attribute(("src", file.as_str()));
+        context.files.push((file, data));
+    }
+
+    Ok(element)
+}
+
+fn write_node(
+    writer: &mut XmlWriter,
+    name: &str,
+    node: &WzNodeArc,
+    relative_path: &str,
+    context: &mut XmlExportContext,
+) -> Result<()> {
+    // the children of image are only available after parsed
+    let is_image = node.read().unwrap().try_as_image().is_some();
+    if is_image {
+        node_util::parse_node(node)?;
+    }
+
+    // resolve_png need to lock the node again, so resolve it before holding the lock
+    let canvas_element = if is_canvas(&node.read().unwrap()) {
+        Some(get_canvas_element(name, node, relative_path, context)?)
+    } else {
+        None
+    };
+
+    let node_read = node.read().unwrap();
+
+    if let Some(element) = get_value_element(name, &node_read) {
+        writer.write_event(Event::Empty(element))?;
+        return Ok(());
+    }
+
+    let element = if let Some(element) = canvas_element {
+        element
+    } else if is_sound(&node_read) {
+        get_sound_element(name, &node_read, relative_path, context)?
+    } else {
+        let tag = match &node_read.object_type {
+            WzObjectType::Property(WzSubProperty::Property) => "imgdir",
+            WzObjectType::Property(_) => "extended",
+            // image, wz file and folder
+            _ => "imgdir",
+        };
+        BytesStart::new(tag).with_attributes([("name", name)])
+    };
+
+    let children = get_sorted_children(&node_read);
+
+    if children.is_empty() {
+        writer.write_event(Event::Empty(element))?;
+        return Ok(());
+    }
+
+    let end = element.to_end().into_owned();
+    writer.write_event(Event::Start(element))?;
+
+    for (child_name, child) in children {
+        let child_path = if relative_path.is_empty() {
+            child_name.clone()
+        } else {
+            format!("{}/{}", relative_path, child_name)
+        };
+        write_node(writer, &child_name, &child, &child_path, context)?;
+    }
+
+    writer.write_event(Event::End(end))?;
+
+    Ok(())
+}
+
+/// serialize the node subtree into the HaRepacker xml dump format.
+///
+/// canvas and sound are written as base64 `basedata` when `inline_media`,
+/// otherwise as side files referenced by the `src` attribute
+pub fn resolve_xml_export(
+    node: &WzNodeArc,
+    root: Option<&WzNodeArc>,
+    inline_media: bool,
+) -> Result<XmlExport> {
+    let name = node.read().unwrap().name.to_string();
+    let mut context = XmlExportContext {
+        root,
+        inline_media,
+        files: Vec::new(),
+    };
+
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new(
+        "1.0",
+        Some("UTF-8"),
+        Some("yes"),
+    )))?;
+
+    write_node(&mut writer, &name, node, &name, &mut context)?;
+
+    let mut xml = writer.into_inner();
+    if !xml.ends_with(b"\n") {
+        xml.push(b'\n');
+    }
+
+    Ok(XmlExport {
+        name,
+        xml,
+        files: context.files,
+    })
+}
+
+pub fn get_xml_file_name(export: &XmlExport) -> String {
+    format!("{}.xml", sanitize_file_name(&export.name))
+}
+
+/// the zip of the xml and its side files
+pub fn get_xml_zip_name(export: &XmlExport) -> String {
+    format!("{}.zip", sanitize_file_name(&export.name))
+}
+
+pub fn write_xml_export_zip(export: &XmlExport) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(get_xml_file_name(export), options)?;
+    zip.write_all(&export.xml)?;
+
+    for (file, data) in export.files.iter() {
+        zip.start_file(file.as_str(), options)?;
+        zip.write_all(data)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// write `<name>.xml` and the side files into `output`, return the xml path
+pub fn save_xml_export(export: &XmlExport, output: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(output)?;
+
+    for (file, data) in export.files.iter() {
+        let file_path = output.join(file);
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file_path, data)?;
+    }
+
+    let xml_path = output.join(get_xml_file_name(export));
+    std::fs::write(&xml_path, &export.xml)?;
+
+    Ok(xml_path)
+}
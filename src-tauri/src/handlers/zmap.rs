@@ -6,7 +6,9 @@ use crate::Result;
 pub fn get_zmap(root: &WzNodeArc) -> Result<WzNodeArc> {
     let node = root.read().unwrap();
 
-    let node = node.at(ZMAP_PATH).ok_or(crate::Error::NodeNotFound)?;
+    let node = node
+        .at(ZMAP_PATH)
+        .ok_or_else(|| crate::Error::node_not_found(&node, ZMAP_PATH))?;
 
     Ok(node)
 }
@@ -14,7 +16,9 @@ pub fn get_zmap(root: &WzNodeArc) -> Result<WzNodeArc> {
 pub fn resolve_zmap(node: &WzNodeArc) -> Result<Vec<String>> {
     let node = node.read().unwrap();
 
-    let image = node.try_as_image().ok_or(crate::Error::NodeNotFound)?;
+    let image = node
+        .try_as_image()
+        .ok_or(crate::Error::NodeTypeMismatch("WzImage"))?;
 
     let child = image.resolve_children(None).map_err(node::Error::from)?;
 
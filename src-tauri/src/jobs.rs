@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime};
+use wz_reader::WzNodeArc;
+
+use crate::memory::{ImageTracker, SharedImageTracker};
+use crate::{handlers, Error, MountSkillCache, Result};
+
+pub const EXPORT_PROGRESS_EVENT: &'static str = "export://progress";
+
+/// what a export job should export
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ExportTarget {
+    /// a list of skill id
+    Skills { ids: Vec<String> },
+    /// every skill in a job folder, like `2312` or `2312.img`
+    JobFolder { folder: String },
+    /// every item in the catalog whose name or id contains the keyword
+    Catalog {
+        catalog: ExportCatalog,
+        keyword: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportCatalog {
+    Skill,
+    Mount,
+}
+
+#[derive(Debug, Clone)]
+enum ExportItem {
+    Skill(String),
+    /// (mount id, name)
+    Mount(String, String),
+}
+
+/// the loaded data a job reads, taken from the app state when the job starts
+pub struct JobSource {
+    pub root: WzNodeArc,
+    pub images: SharedImageTracker,
+    pub mount_skills: MountSkillCache,
+    pub mount_overrides: HashMap<String, String>,
+}
+
+impl ExportItem {
+    fn label(&self) -> String {
+        match self {
+            ExportItem::Skill(id) => format!(
+                "{}/{}/skill/{}",
+                handlers::path::SKILL_PATH,
+                handlers::get_skill_image_name(id),
+                id
+            ),
+            ExportItem::Mount(id, _) => handlers::get_mount_image_path(id),
+        }
+    }
+
+    /// only the images the export parsed, walking the whole tree per item is too slow
+    fn track_images(&self, root: &WzNodeArc, images: &ImageTracker) {
+        match self {
+            ExportItem::Skill(id) => handlers::track_skill_bundle_images(root, id, images),
+            ExportItem::Mount(id, _) => handlers::track_mount_bundle_images(root, id, images),
+        }
+    }
+
+    fn export(&self, root: &WzNodeArc, output: &Path, as_folder: bool) -> Result<()> {
+        let bundle = match self {
+            ExportItem::Skill(id) => handlers::resolve_skill_bundle(root, id)?,
+            ExportItem::Mount(id, name) => handlers::resolve_mount_bundle(root, id, name)?,
+        };
+
+        handlers::save_skill_bundle(&bundle, output, as_folder)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Finished,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// payload of `export://progress`, also used as the listing of jobs
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProgress {
+    pub job_id: u32,
+    pub status: JobStatus,
+    pub done: usize,
+    pub total: usize,
+    pub current: String,
+    pub output: String,
+    pub failures: Vec<JobFailure>,
+}
+
+pub struct ExportJob {
+    id: u32,
+    output: PathBuf,
+    as_folder: bool,
+    status: Mutex<JobStatus>,
+    done: AtomicUsize,
+    total: AtomicUsize,
+    current: Mutex<String>,
+    failures: Mutex<Vec<JobFailure>>,
+    cancelled: AtomicBool,
+}
+
+impl ExportJob {
+    pub fn progress(&self) -> ExportProgress {
+        ExportProgress {
+            job_id: self.id,
+            status: *self.status.lock().unwrap(),
+            done: self.done.load(Ordering::Relaxed),
+            total: self.total.load(Ordering::Relaxed),
+            current: self.current.lock().unwrap().clone(),
+            output: self.output.to_string_lossy().to_string(),
+            failures: self.failures.lock().unwrap().clone(),
+        }
+    }
+
+    /// a job no longer running keeps its status
+    pub fn cancel(&self) {
+        let status = self.status.lock().unwrap();
+        if matches!(*status, JobStatus::Pending | JobStatus::Running) {
+            self.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn set_status(&self, status: JobStatus) {
+        *self.status.lock().unwrap() = status;
+    }
+
+    fn emit<R: Runtime>(&self, app: &AppHandle<R>) {
+        let _ = app.emit(EXPORT_PROGRESS_EVENT, self.progress());
+    }
+
+    fn run<R: Runtime>(&self, app: &AppHandle<R>, target: &ExportTarget, source: &JobSource) {
+        let JobSource { root, images, .. } = source;
+
+        self.set_status(JobStatus::Running);
+        self.emit(app);
+
+        let items = match resolve_export_items(target, source) {
+            Ok(items) => items,
+            Err(e) => {
+                self.failures.lock().unwrap().push(JobFailure {
+                    path: String::new(),
+                    error: e.to_string(),
+                });
+                self.set_status(JobStatus::Failed);
+                self.emit(app);
+                return;
+            }
+        };
+
+        self.total.store(items.len(), Ordering::Relaxed);
+        self.emit(app);
+
+        items.par_iter().for_each(|item| {
+            if self.is_cancelled() {
+                return;
+            }
+
+            let label = item.label();
+            *self.current.lock().unwrap() = label.clone();
+
+            let result = {
+                // the other items may release images meanwhile, keep this one parsed
+                let _pin = images.pin(&label);
+                item.export(root, &self.output, self.as_folder)
+            };
+            if let Err(e) = result {
+                self.failures.lock().unwrap().push(JobFailure {
+                    path: label,
+                    error: e.to_string(),
+                });
+            }
+
+            // release the images of the exported items when over the budget
+            item.track_images(root, images);
+            images.enforce();
+
+            self.done.fetch_add(1, Ordering::Relaxed);
+            self.emit(app);
+        });
+
+        // a cancel after the last item doesn't skip anything
+        let skipped = self.done.load(Ordering::Relaxed) < items.len();
+        self.set_status(if self.is_cancelled() && skipped {
+            JobStatus::Cancelled
+        } else {
+            JobStatus::Finished
+        });
+        self.emit(app);
+    }
+}
+
+fn resolve_export_items(target: &ExportTarget, source: &JobSource) -> Result<Vec<ExportItem>> {
+    let root = &source.root;
+
+    let items = match target {
+        ExportTarget::Skills { ids } => ids.iter().cloned().map(ExportItem::Skill).collect(),
+        ExportTarget::JobFolder { folder } => {
+            let folder = folder.trim_end_matches(".img");
+            handlers::resolve_skill_string(root)?
+                .into_iter()
+                .filter(|(_, parent_folder, _)| parent_folder.trim_end_matches(".img") == folder)
+                .map(|(id, _, _)| ExportItem::Skill(id))
+                .collect()
+        }
+        ExportTarget::Catalog { catalog, keyword } => {
+            let keyword = keyword.as_deref().unwrap_or("").to_lowercase();
+            let is_match = |id: &str, name: &str| {
+                id.contains(&keyword) || name.to_lowercase().contains(&keyword)
+            };
+
+            match catalog {
+                ExportCatalog::Skill => handlers::resolve_skill_string(root)?
+                    .into_iter()
+                    .filter(|(id, _, name)| is_match(id, name))
+                    .map(|(id, _, _)| ExportItem::Skill(id))
+                    .collect(),
+                ExportCatalog::Mount => {
+                    let mount_skill_map = handlers::resolve_mount_skill_map(
+                        root,
+                        &source.mount_skills,
+                        &source.mount_overrides,
+                        &source.images,
+                    );
+                    handlers::resolve_mount_string(root, &mount_skill_map)?
+                        .into_iter()
+                        .filter(|(id, name)| is_match(id, name))
+                        .map(|(id, name)| ExportItem::Mount(id, name))
+                        .collect()
+                }
+            }
+        }
+    };
+
+    Ok(items)
+}
+
+#[derive(Default)]
+pub struct JobRegistry {
+    next_id: AtomicU32,
+    jobs: RwLock<HashMap<u32, Arc<ExportJob>>>,
+}
+
+impl JobRegistry {
+    /// create a job and run it on the rayon pool, return the job id immediately
+    pub fn start<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        target: ExportTarget,
+        output: PathBuf,
+        as_folder: bool,
+        source: JobSource,
+    ) -> u32 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let job = Arc::new(ExportJob {
+            id,
+            output,
+            as_folder,
+            status: Mutex::new(JobStatus::Pending),
+            done: AtomicUsize::new(0),
+            total: AtomicUsize::new(0),
+            current: Mutex::new(String::new()),
+            failures: Mutex::new(Vec::new()),
+            cancelled: AtomicBool::new(false),
+        });
+
+        self.jobs.write().unwrap().insert(id, Arc::clone(&job));
+
+        rayon::spawn(move || job.run(&app, &target, &source));
+
+        id
+    }
+
+    pub fn list(&self) -> Vec<ExportProgress> {
+        let mut jobs = self
+            .jobs
+            .read()
+            .unwrap()
+            .values()
+            .map(|job| job.progress())
+            .collect::<Vec<_>>();
+
+        jobs.sort_by_key(|job| job.job_id);
+
+        jobs
+    }
+
+    pub fn cancel(&self, id: u32) -> Result<()> {
+        self.jobs
+            .read()
+            .unwrap()
+            .get(&id)
+            .map(|job| job.cancel())
+            .ok_or(Error::JobNotFound)
+    }
+
+    /// remove every job that no longer running
+    pub fn clear_finished(&self) {
+        self.jobs.write().unwrap().retain(|_, job| {
+            matches!(
+                *job.status.lock().unwrap(),
+                JobStatus::Pending | JobStatus::Running
+            )
+        });
+    }
+}
@@ -0,0 +1,26 @@
+mod error;
+mod store;
+
+#[cfg(feature = "desktop")]
+mod commands;
+#[cfg(feature = "desktop")]
+mod desktop;
+#[cfg(feature = "desktop")]
+mod jobs;
+
+pub mod cache;
+pub mod handlers;
+pub mod link_index;
+pub mod memory;
+pub mod models;
+pub mod server;
+pub mod stats;
+pub mod utils;
+
+#[cfg(feature = "desktop")]
+pub use desktop::run;
+#[cfg(feature = "desktop")]
+pub use store::AppStore;
+pub use store::{MountSkillCache, MountSkillOverrides, PackSources, StringDict};
+
+pub use error::{Error, Result};
@@ -0,0 +1,218 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use wz_reader::{
+    property::string::resolve_string_from_node, util::node_util, WzNodeArc, WzNodeCast,
+};
+
+use crate::memory::{get_relative_path, ImageTracker, SharedImageTracker};
+use crate::models::LinkKind;
+use crate::Result;
+
+/// how often the background indexer looks for newly parsed images
+const INDEX_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkReference {
+    pub path: String,
+    pub kind: LinkKind,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkReferences {
+    pub target: String,
+    pub references: Vec<LinkReference>,
+    /// the result only covers the indexed images
+    pub indexed_images: usize,
+}
+
+#[derive(Default)]
+struct LinkIndexInner {
+    /// target path -> the nodes link to it
+    references: HashMap<String, Vec<LinkReference>>,
+    indexed_images: HashSet<String>,
+}
+
+/// reverse index of _inlink, _outlink and uol, built from the parsed images
+pub struct LinkIndex {
+    inner: RwLock<LinkIndexInner>,
+}
+
+pub type SharedLinkIndex = Arc<LinkIndex>;
+
+/// join the relative path like `../../0` to the base path
+fn join_relative_path(base: &str, relative: &str) -> String {
+    let mut segments = base
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+
+    for segment in relative.split('/').filter(|s| !s.is_empty()) {
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." => {}
+            segment => segments.push(segment),
+        }
+    }
+
+    segments.join("/")
+}
+
+fn get_parent_path(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+/// (source path, target path, kind) of every link under the node
+fn collect_links(
+    node: &WzNodeArc,
+    path: &str,
+    image_path: &str,
+    result: &mut Vec<(String, String, LinkKind)>,
+) {
+    let node_read = node.read().unwrap();
+
+    if let Some(uol) = node_read.try_as_uol() {
+        if let Ok(target) = uol.get_string() {
+            let target = join_relative_path(get_parent_path(path), &target);
+            result.push((path.to_string(), target, LinkKind::Uol));
+        }
+        return;
+    }
+
+    if node_read.try_as_png().is_some() {
+        let inlink = node_read
+            .at("_inlink")
+            .and_then(|node| resolve_string_from_node(&node).ok());
+        if let Some(link) = inlink {
+            let target = join_relative_path(image_path, &link);
+            result.push((path.to_string(), target, LinkKind::Inlink));
+        }
+
+        let outlink = node_read
+            .at("_outlink")
+            .and_then(|node| resolve_string_from_node(&node).ok());
+        if let Some(link) = outlink {
+            let target = join_relative_path("", &link);
+            result.push((path.to_string(), target, LinkKind::Outlink));
+        }
+    }
+
+    for (name, child) in node_read.children.iter() {
+        collect_links(child, &format!("{}/{}", path, name), image_path, result);
+    }
+}
+
+impl LinkIndex {
+    pub fn new() -> SharedLinkIndex {
+        Arc::new(LinkIndex {
+            inner: RwLock::new(LinkIndexInner::default()),
+        })
+    }
+
+    /// index the links of a parsed image, do nothing if indexed already
+    pub fn index_image(&self, image_node: &WzNodeArc) {
+        let image_path = get_relative_path(image_node);
+
+        if self.inner.read().unwrap().indexed_images.contains(&image_path) {
+            return;
+        }
+
+        if image_node.read().unwrap().children.is_empty() {
+            return;
+        }
+
+        let mut links = Vec::new();
+        collect_links(image_node, &image_path, &image_path, &mut links);
+
+        let mut inner = self.inner.write().unwrap();
+        // another thread may index the same image meanwhile
+        if !inner.indexed_images.insert(image_path) {
+            return;
+        }
+        for (source, target, kind) in links {
+            inner
+                .references
+                .entry(target)
+                .or_default()
+                .push(LinkReference { path: source, kind });
+        }
+    }
+
+    /// parse and index every image under the node, return the count of indexed images.
+    ///
+    /// the parsed images are tracked, so the memory budget releases them while indexing
+    pub fn index_subtree(&self, node: &WzNodeArc, images: &ImageTracker) -> Result<usize> {
+        let is_image = node.read().unwrap().try_as_image().is_some();
+
+        if is_image {
+            node_util::parse_node(node)?;
+            self.index_image(node);
+            images.track(node);
+            images.enforce();
+            return Ok(1);
+        }
+
+        let children = node
+            .read()
+            .unwrap()
+            .children
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut count = 0;
+        for child in children {
+            count += self.index_subtree(&child, images)?;
+        }
+
+        Ok(count)
+    }
+
+    /// the nodes link to the target, or to any node under the target
+    pub fn get_references(&self, target: &str) -> LinkReferences {
+        let target = target.trim_matches('/').to_string();
+        let prefix = format!("{}/", target);
+        let inner = self.inner.read().unwrap();
+
+        let mut references = inner
+            .references
+            .iter()
+            .filter(|(path, _)| **path == target || path.starts_with(&prefix))
+            .flat_map(|(_, references)| references.iter().cloned())
+            .collect::<Vec<_>>();
+        references.sort_by(|a, b| a.path.cmp(&b.path));
+
+        LinkReferences {
+            target,
+            references,
+            indexed_images: inner.indexed_images.len(),
+        }
+    }
+
+    /// forget everything, used when the root is replaced
+    pub fn clear(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.references.clear();
+        inner.indexed_images.clear();
+    }
+
+    /// index the images parsed by requests in background
+    pub fn spawn_indexer(self: &Arc<Self>, images: SharedImageTracker) {
+        let index = Arc::clone(self);
+
+        thread::spawn(move || loop {
+            thread::sleep(INDEX_INTERVAL);
+
+            for image_node in images.get_tracked_nodes() {
+                index.index_image(&image_node);
+            }
+        });
+    }
+}
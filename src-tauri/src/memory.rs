@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use wz_reader::{
+    property::WzSubProperty, util::node_util, WzNode, WzNodeArc, WzNodeCast, WzObjectType,
+};
+
+/// rough memory held by a parsed node, the png and sound data are still in the mmap
+pub const NODE_ESTIMATE_BYTES: u64 = 160;
+
+struct TrackedImage {
+    node: WzNodeArc,
+    last_access: Instant,
+    estimated_bytes: u64,
+}
+
+/// track the parsed images and unparse the least recently used ones when over the budget
+pub struct ImageTracker {
+    root: WzNodeArc,
+    /// 0 means no limit
+    budget: AtomicU64,
+    images: Mutex<HashMap<String, TrackedImage>>,
+    /// path of in-flight requests -> request count
+    pins: Mutex<HashMap<String, usize>>,
+}
+
+pub type SharedImageTracker = Arc<ImageTracker>;
+
+/// keep the images along the path parsed until dropped
+pub struct ImagePin {
+    tracker: SharedImageTracker,
+    path: String,
+}
+
+impl Drop for ImagePin {
+    fn drop(&mut self) {
+        let mut pins = self.tracker.pins.lock().unwrap();
+        if let Some(count) = pins.get_mut(&self.path) {
+            *count -= 1;
+            if *count == 0 {
+                pins.remove(&self.path);
+            }
+        }
+    }
+}
+
+fn count_nodes(node: &WzNode) -> u64 {
+    1 + node
+        .children
+        .values()
+        .map(|child| count_nodes(&child.read().unwrap()))
+        .sum::<u64>()
+}
+
+/// the path from root, without the root name
+pub fn get_relative_path(node: &WzNodeArc) -> String {
+    let mut names = Vec::new();
+    let mut current = node.clone();
+
+    loop {
+        let parent = {
+            let current_read = current.read().unwrap();
+            let Some(parent) = current_read.parent.upgrade() else {
+                break;
+            };
+            names.push(current_read.name.to_string());
+            parent
+        };
+        current = parent;
+    }
+
+    names.reverse();
+    names.join("/")
+}
+
+/// the estimated bytes of a parsed image, None if the node is not a parsed image
+fn get_image_bytes(node: &WzNode) -> Option<u64> {
+    if node.try_as_image().is_none() || node.children.is_empty() {
+        return None;
+    }
+    Some(count_nodes(node) * NODE_ESTIMATE_BYTES)
+}
+
+/// the nodes may contain images, the folders of a dump or a pack are properties
+fn is_image_container(node: &WzNode) -> bool {
+    match &node.object_type {
+        WzObjectType::Directory(_) | WzObjectType::File(_) | WzObjectType::MsFile(_) => true,
+        WzObjectType::Property(WzSubProperty::Property) => !node.name.ends_with(".img"),
+        _ => false,
+    }
+}
+
+/// both path are the same or one is the ancestor of another
+fn is_path_overlapped(a: &str, b: &str) -> bool {
+    let is_ancestor = |parent: &str, child: &str| {
+        child.len() > parent.len()
+            && child.starts_with(parent)
+            && child.as_bytes()[parent.len()] == b'/'
+    };
+
+    a == b || is_ancestor(a, b) || is_ancestor(b, a)
+}
+
+impl ImageTracker {
+    pub fn new(root: WzNodeArc) -> SharedImageTracker {
+        Arc::new(ImageTracker {
+            root,
+            budget: AtomicU64::new(0),
+            images: Mutex::new(HashMap::new()),
+            pins: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn set_budget(&self, bytes: u64) {
+        self.budget.store(bytes, Ordering::Relaxed);
+    }
+
+    pub fn get_budget(&self) -> u64 {
+        self.budget.load(Ordering::Relaxed)
+    }
+
+    pub fn pin(self: &Arc<Self>, path: &str) -> ImagePin {
+        let path = path.trim_matches('/').to_string();
+        *self.pins.lock().unwrap().entry(path.clone()).or_insert(0) += 1;
+
+        ImagePin {
+            tracker: Arc::clone(self),
+            path,
+        }
+    }
+
+    /// record the access of the image which the path belongs to, only parsed image is tracked.
+    ///
+    /// return whether the image was already tracked, None if there is no parsed image
+    pub fn touch(&self, path: &str) -> Option<bool> {
+        let (image_node, _) = node_util::get_image_node_from_path(&self.root, path)?;
+
+        let key = get_relative_path(&image_node);
+
+        if let Some(image) = self.images.lock().unwrap().get_mut(&key) {
+            image.last_access = Instant::now();
+            return Some(true);
+        }
+
+        self.track(&image_node).then_some(false)
+    }
+
+    /// track a parsed image, the last access of a tracked one is kept.
+    ///
+    /// return false if the node is not a parsed image
+    pub fn track(&self, image_node: &WzNodeArc) -> bool {
+        let key = get_relative_path(image_node);
+
+        if self.images.lock().unwrap().contains_key(&key) {
+            return true;
+        }
+
+        let Some(estimated_bytes) = get_image_bytes(&image_node.read().unwrap()) else {
+            return false;
+        };
+
+        self.images
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| TrackedImage {
+                node: image_node.clone(),
+                last_access: Instant::now(),
+                estimated_bytes,
+            });
+
+        true
+    }
+
+    /// track every parsed image under the node, or the image the node belongs to,
+    /// for the requests parse many images at once
+    pub fn track_parsed(&self, node: &WzNodeArc) {
+        let mut current = Some(node.clone());
+        while let Some(ancestor) = current {
+            if ancestor.read().unwrap().try_as_image().is_some() {
+                self.track(&ancestor);
+                return;
+            }
+            current = ancestor.read().unwrap().parent.upgrade();
+        }
+
+        self.track_parsed_children(node);
+    }
+
+    fn track_parsed_children(&self, node: &WzNodeArc) {
+        let children = {
+            let node_read = node.read().unwrap();
+            if node_read.try_as_image().is_some() {
+                drop(node_read);
+                self.track(node);
+                return;
+            }
+            if !is_image_container(&node_read) {
+                return;
+            }
+            node_read.children.values().cloned().collect::<Vec<_>>()
+        };
+
+        for child in children {
+            self.track_parsed_children(&child);
+        }
+    }
+
+    /// (parsed image count, estimated bytes) of the tracked images
+    pub fn get_usage(&self) -> (usize, u64) {
+        let images = self.images.lock().unwrap();
+
+        (
+            images.len(),
+            images.values().map(|image| image.estimated_bytes).sum(),
+        )
+    }
+
+    pub fn get_tracked_nodes(&self) -> Vec<WzNodeArc> {
+        self.images
+            .lock()
+            .unwrap()
+            .values()
+            .map(|image| image.node.clone())
+            .collect()
+    }
+
+    /// unparse the least recently used images until the usage is under the budget
+    pub fn enforce(&self) {
+        let budget = self.get_budget();
+        if budget == 0 {
+            return;
+        }
+
+        let mut images = self.images.lock().unwrap();
+
+        // the image may be unparsed by `/node/unparse` already
+        images.retain(|_, image| !image.node.read().unwrap().children.is_empty());
+
+        let mut usage = images.values().map(|image| image.estimated_bytes).sum::<u64>();
+        if usage <= budget {
+            return;
+        }
+
+        let mut candidates = images
+            .iter()
+            .map(|(path, image)| (path.clone(), image.last_access))
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(_, last_access)| *last_access);
+
+        let pins = self.pins.lock().unwrap();
+
+        for (path, _) in candidates {
+            if usage <= budget {
+                break;
+            }
+            if pins.keys().any(|pin| is_path_overlapped(pin, &path)) {
+                continue;
+            }
+            if let Some(image) = images.remove(&path) {
+                image.node.write().unwrap().unparse();
+                usage -= image.estimated_bytes;
+            }
+        }
+    }
+
+    /// forget every tracked image, used when the root is replaced
+    pub fn clear(&self) {
+        self.images.lock().unwrap().clear();
+    }
+}
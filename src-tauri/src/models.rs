@@ -1,4 +1,5 @@
 use serde::Serialize;
+use serde_json::Value;
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -7,4 +8,56 @@ pub struct NodeInfo {
     pub _type: String,
     pub name: String,
     pub has_child: bool,
+    /// the pack file the node came from, only for nodes loaded from Packs/*.ms
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pack: Option<String>,
+    /// number, string or [x, y] of vector, long string is truncated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub png: Option<PngInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sound: Option<SoundInfo>,
+    /// the target of _inlink, _outlink or uol
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<LinkInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PngInfo {
+    pub width: u32,
+    pub height: u32,
+    pub format: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoundInfo {
+    /// in milliseconds
+    pub duration: u32,
+    pub codec: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkKind {
+    Inlink,
+    Outlink,
+    Uol,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkInfo {
+    pub kind: LinkKind,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeInfoPage {
+    pub total: usize,
+    pub offset: usize,
+    pub items: Vec<NodeInfo>,
 }
@@ -0,0 +1,99 @@
+use std::sync::{Arc, RwLock};
+
+use axum::{extract::Request, http::HeaderValue};
+
+pub const TOKEN_HEADER: &str = "x-wz-token";
+pub const TOKEN_QUERY: &str = "token";
+
+/// the origins of the webview, `http://tauri.localhost` is used on windows
+const TAURI_ORIGINS: [&str; 3] = [
+    "tauri://localhost",
+    "http://tauri.localhost",
+    "https://tauri.localhost",
+];
+
+/// the vite dev server in tauri.conf.json
+const DEV_ORIGIN: &str = "http://localhost:8041";
+
+/// the session token and the origins allowed to call the local server
+pub struct ServerAccess {
+    token: String,
+    allowed_origins: RwLock<Vec<String>>,
+}
+
+pub type SharedServerAccess = Arc<ServerAccess>;
+
+/// 256 bits from the os random source
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).expect("the os random source is unavailable");
+
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// compare without returning early, so the timing tells nothing about the token
+fn is_token_equal(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn get_query_token(query: &str) -> Option<&str> {
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == TOKEN_QUERY).then_some(value)
+    })
+}
+
+impl ServerAccess {
+    pub fn new() -> SharedServerAccess {
+        Self::with_token(generate_token())
+    }
+
+    /// a fixed token, so the clients can keep it across restarts
+    pub fn with_token(token: String) -> SharedServerAccess {
+        Arc::new(ServerAccess {
+            token,
+            allowed_origins: RwLock::new(Vec::new()),
+        })
+    }
+
+    pub fn get_token(&self) -> &str {
+        &self.token
+    }
+
+    /// the extra origins besides the webview, like the web version of the tool
+    pub fn set_allowed_origins(&self, origins: Vec<String>) {
+        *self.allowed_origins.write().unwrap() = origins
+            .into_iter()
+            .map(|origin| origin.trim_end_matches('/').to_string())
+            .filter(|origin| !origin.is_empty())
+            .collect();
+    }
+
+    pub fn is_origin_allowed(&self, origin: &HeaderValue) -> bool {
+        let Ok(origin) = origin.to_str() else {
+            return false;
+        };
+
+        TAURI_ORIGINS.contains(&origin)
+            || (cfg!(debug_assertions) && origin == DEV_ORIGIN)
+            || self
+                .allowed_origins
+                .read()
+                .unwrap()
+                .iter()
+                .any(|allowed| allowed == origin)
+    }
+
+    /// the token from the header, or from the query for `<img src>` and `<audio src>`
+    pub fn is_authorized(&self, req: &Request) -> bool {
+        let token = req
+            .headers()
+            .get(TOKEN_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .or_else(|| req.uri().query().and_then(get_query_token));
+
+        token.map_or(false, |token| {
+            is_token_equal(token.as_bytes(), self.token.as_bytes())
+        })
+    }
+}
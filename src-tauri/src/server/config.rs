@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use super::{ServerOptions, DEFAULT_PORT};
+use crate::Result;
+
+const MEGABYTE: u64 = 1024 * 1024;
+
+fn default_bind() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+/// the config file of the standalone server, like
+/// `{ "bind": "0.0.0.0", "basePath": "/data/wz/Base.wz", "region": "GMS" }`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerConfig {
+    #[serde(default = "default_bind")]
+    pub bind: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// path of Base.wz, or a folder of .img or .xml dump
+    pub base_path: String,
+    /// region preset (GMS, KMS, MSEA...), AUTO or a custom iv in hex
+    pub region: Option<String>,
+    /// pack families to load from `Packs/*.ms`, None means every pack
+    pub pack_families: Option<Vec<String>>,
+    #[serde(default)]
+    pub read_only: bool,
+    /// the origins besides the webview can call the server
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// a fixed access token, a random one is generated if absent
+    pub token: Option<String>,
+    /// size of the encoded image cache
+    pub image_cache_mb: Option<u64>,
+    /// unparse the least recently used images over the budget, no limit if absent
+    pub memory_budget_mb: Option<u64>,
+    /// a json of mount id to skill id, replaces the mapping from the skill data
+    pub mount_skill_overrides: Option<String>,
+}
+
+impl ServerConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    pub fn get_options(&self) -> ServerOptions {
+        ServerOptions {
+            host: self.bind.clone(),
+            port: self.port,
+            read_only: self.read_only,
+        }
+    }
+
+    pub fn get_image_cache_bytes(&self) -> Option<usize> {
+        self.image_cache_mb.map(|megabytes| (megabytes * MEGABYTE) as usize)
+    }
+
+    pub fn get_memory_budget_bytes(&self) -> Option<u64> {
+        self.memory_budget_mb.map(|megabytes| megabytes * MEGABYTE)
+    }
+}
@@ -0,0 +1,239 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::IntoResponse,
+    Extension, Json,
+};
+use tokio::task::spawn_blocking;
+use wz_reader::WzNodeArc;
+
+use crate::memory::SharedImageTracker;
+use crate::server::extractors::TargetNodeExtractor;
+use crate::server::models::{GetXmlParam, RenderAnimationParam, SkillTimelineParam};
+use crate::{handlers, Error, Result};
+
+use super::super::AppState;
+
+pub async fn get_skill_bundle(
+    State((root, _)): State<AppState>,
+    Extension(images): Extension<SharedImageTracker>,
+    Path(skill_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let id = skill_id.clone();
+    let zip = spawn_blocking(move || {
+        let bundle = handlers::resolve_skill_bundle(&root, &id);
+
+        handlers::track_skill_bundle_images(&root, &id, &images);
+        images.enforce();
+
+        handlers::write_skill_bundle_zip(&bundle?)
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.zip\"", skill_id),
+            ),
+        ],
+        zip,
+    ))
+}
+
+fn get_gif_options(alpha_threshold: Option<u8>, matte: Option<[u8; 3]>) -> handlers::GifOptions {
+    let default = handlers::GifOptions::default();
+
+    handlers::GifOptions {
+        alpha_threshold: alpha_threshold.unwrap_or(default.alpha_threshold),
+        matte,
+        ..default
+    }
+}
+
+fn get_timeline_options(param: &SkillTimelineParam) -> handlers::TimelineOptions {
+    handlers::TimelineOptions {
+        hit_delay: param.hit_delay,
+        target_offset: (param.target_x.unwrap_or(0), param.target_y.unwrap_or(0)),
+    }
+}
+
+/// the playback plan of every effect layer, without decoding the images
+pub async fn get_skill_timeline(
+    State((root, _)): State<AppState>,
+    Extension(images): Extension<SharedImageTracker>,
+    Path(skill_id): Path<String>,
+    Query(param): Query<SkillTimelineParam>,
+) -> Result<impl IntoResponse> {
+    let timeline = spawn_blocking(move || {
+        let timeline =
+            handlers::resolve_skill_timeline(&root, &skill_id, &get_timeline_options(&param));
+
+        handlers::track_skill_image(&root, &skill_id, &images);
+        images.enforce();
+
+        timeline
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
+
+    Ok(Json(timeline))
+}
+
+/// every effect layer composed into one animation, the format is `webp`, `apng` or `gif`
+pub async fn get_skill_timeline_animation(
+    State((root, _)): State<AppState>,
+    Extension(images): Extension<SharedImageTracker>,
+    Path((skill_id, format)): Path<(String, handlers::AnimationFormat)>,
+    Query(param): Query<SkillTimelineParam>,
+) -> Result<impl IntoResponse> {
+    let id = skill_id.clone();
+    let data = spawn_blocking(move || {
+        let timeline = handlers::resolve_skill_timeline(&root, &id, &get_timeline_options(&param));
+
+        handlers::track_skill_image(&root, &id, &images);
+        images.enforce();
+
+        let animation = handlers::render_skill_timeline(&root, &timeline?)?;
+        let gif_options = get_gif_options(param.alpha_threshold, param.matte);
+        handlers::encode_composed(&animation, format, &gif_options)
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.get_mime_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "inline; filename=\"{}.{}\"",
+                    skill_id,
+                    format.get_extension()
+                ),
+            ),
+        ],
+        data,
+    ))
+}
+
+/// a single xml when media is inline, otherwise a zip with the xml and side files
+pub async fn get_xml(
+    State((root, _)): State<AppState>,
+    Extension(images): Extension<SharedImageTracker>,
+    Query(param): Query<GetXmlParam>,
+    TargetNodeExtractor(node): TargetNodeExtractor,
+) -> Result<impl IntoResponse> {
+    let inline = param.inline.unwrap_or(false);
+
+    let (content_type, file_name, data) = spawn_blocking(move || -> Result<_> {
+        let export = handlers::resolve_xml_export(&node, Some(&root), inline);
+
+        // the node may be a folder, every image under it is parsed
+        images.track_parsed(&node);
+        images.enforce();
+
+        let export = export?;
+
+        if export.files.is_empty() {
+            let file_name = handlers::get_xml_file_name(&export);
+            return Ok(("application/xml", file_name, export.xml));
+        }
+
+        let zip = handlers::write_xml_export_zip(&export)?;
+
+        Ok(("application/zip", handlers::get_xml_zip_name(&export), zip))
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        data,
+    ))
+}
+
+/// the animation rendered as the client plays it, with the alpha, zoom and move applied
+pub async fn get_animation(
+    State((root, _)): State<AppState>,
+    Extension(images): Extension<SharedImageTracker>,
+    Query(param): Query<RenderAnimationParam>,
+    TargetNodeExtractor(node): TargetNodeExtractor,
+) -> Result<impl IntoResponse> {
+    let options = handlers::RenderOptions {
+        fps: param.fps.unwrap_or(handlers::DEFAULT_SAMPLE_FPS),
+    };
+    let format = param.format.unwrap_or_default();
+    let gif_options = get_gif_options(param.alpha_threshold, param.matte);
+
+    let data = spawn_blocking(move || {
+        let animation = handlers::render_animation(&node, Some(&root), &options);
+
+        images.track_parsed(&node);
+        images.enforce();
+
+        handlers::encode_composed(&animation?, format, &gif_options)
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
+
+    Ok(([(header::CONTENT_TYPE, format.get_mime_type())], data))
+}
+
+async fn get_engine_export(
+    root: WzNodeArc,
+    node: WzNodeArc,
+    images: SharedImageTracker,
+    target: handlers::EngineTarget,
+) -> Result<impl IntoResponse> {
+    let (name, zip) = spawn_blocking(move || -> Result<_> {
+        let export = handlers::resolve_engine_export(&node, Some(&root), target);
+
+        images.track_parsed(&node);
+        images.enforce();
+
+        let export = export?;
+        let zip = handlers::write_engine_export_zip(&export)?;
+
+        Ok((export.name, zip))
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.zip\"", name),
+            ),
+        ],
+        zip,
+    ))
+}
+
+/// a godot SpriteFrames .tres with the atlas png, in a zip
+pub async fn get_godot(
+    State((root, _)): State<AppState>,
+    Extension(images): Extension<SharedImageTracker>,
+    TargetNodeExtractor(node): TargetNodeExtractor,
+) -> Result<impl IntoResponse> {
+    get_engine_export(root, node, images, handlers::EngineTarget::Godot).await
+}
+
+/// the atlas png with a json of sprite rects and pivots, in a zip
+pub async fn get_unity(
+    State((root, _)): State<AppState>,
+    Extension(images): Extension<SharedImageTracker>,
+    TargetNodeExtractor(node): TargetNodeExtractor,
+) -> Result<impl IntoResponse> {
+    get_engine_export(root, node, images, handlers::EngineTarget::Unity).await
+}
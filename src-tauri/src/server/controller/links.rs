@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::IntoResponse,
+    Extension,
+};
+use serde_json::json;
+use tokio::task::spawn_blocking;
+
+use crate::link_index::SharedLinkIndex;
+use crate::memory::SharedImageTracker;
+use crate::{handlers, Error, Result};
+
+use super::super::AppState;
+
+/// the nodes link to the path, only the indexed images are covered
+pub async fn get_references(
+    Extension(link_index): Extension<SharedLinkIndex>,
+    Path(path): Path<String>,
+) -> Result<impl IntoResponse> {
+    let references = link_index.get_references(&path);
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/json")],
+        serde_json::to_string(&references)?,
+    ))
+}
+
+/// parse and index every image under the path, so the references of it are complete
+pub async fn index_subtree(
+    State((root, _)): State<AppState>,
+    Extension(link_index): Extension<SharedLinkIndex>,
+    Extension(images): Extension<SharedImageTracker>,
+    Path(path): Path<String>,
+) -> Result<impl IntoResponse> {
+    let indexed = spawn_blocking(move || {
+        let node = handlers::path::get_node_parsed(&root, &path)?;
+        link_index.index_subtree(&node, &images)
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/json")],
+        json!({ "indexed": indexed }).to_string(),
+    ))
+}
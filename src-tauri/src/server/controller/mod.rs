@@ -1,20 +1,45 @@
-use axum::{routing::get, Router};
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
 
-use super::AppState;
+use super::{middlewares, AppState};
 
+pub mod export;
+pub mod links;
 pub mod mapping;
 pub mod node;
+pub mod openapi;
+pub mod stats;
 pub mod string;
 
-pub fn node_router() -> Router<AppState> {
+pub fn node_router(read_only: bool) -> Router<AppState> {
     // 只能有一个 Router::new() 链
-    Router::new()
+    // the encoded images are cached
+    let image_router = Router::new()
         .route("/image/*path", get(node::get_image))
         .route("/image_unparsed/*path", get(node::get_image_unparsed))
+        .route_layer(middleware::from_fn(middlewares::image_cache_middleware));
+
+    let router = Router::new()
+        .merge(image_router)
         .route("/json/*path", get(node::get_json))
+        .route("/batch_image", post(node::batch_image))
+        .route("/search", get(node::search))
+        .route("/duplicates", get(node::get_duplicates))
+        .route("/children", get(node::get_children))
+        .route("/children/*path", get(node::get_children))
         .route("/raw/*path", get(node::get_raw))
         .route("/sound_ogg/*path", get(node::get_ogg_sound)) // <--- 确保这一行在里面
-        .route("/parse/*path", get(node::parse))
+        .route("/parse/*path", get(node::parse));
+
+    if read_only {
+        return router;
+    }
+
+    // these change the loaded tree for every client
+    router
         .route("/unparse/*path", get(node::unparse))
         .route("/load_extra_paths", get(node::load_extra_paths))
 }
@@ -33,6 +58,38 @@ pub fn string_router() -> Router<AppState> {
         .route("/equip/prepare", get(string::prepare_equip))
         .route("/chair", get(string::get_chairs))
         .route("/mount", get(string::get_mounts))
+        .route("/mount/report", get(string::get_mount_report))
         .route("/skill", get(string::get_skills))
         .route("/map", get(string::get_maps))
 }
+
+pub fn export_router() -> Router<AppState> {
+    Router::new()
+        .route("/skill/:id", get(export::get_skill_bundle))
+        .route("/skill_timeline/:id", get(export::get_skill_timeline))
+        .route(
+            "/skill_timeline/:id/:format",
+            get(export::get_skill_timeline_animation),
+        )
+        .route("/xml/*path", get(export::get_xml))
+        .route("/animation/*path", get(export::get_animation))
+        .route("/godot/*path", get(export::get_godot))
+        .route("/unity/*path", get(export::get_unity))
+}
+
+pub fn links_router() -> Router<AppState> {
+    Router::new()
+        .route("/refs/*path", get(links::get_references))
+        .route("/index/*path", get(links::index_subtree))
+}
+
+pub fn stats_router() -> Router<AppState> {
+    Router::new().route("/stats", get(stats::get_stats))
+}
+
+pub fn openapi_router(read_only: bool) -> Router<AppState> {
+    Router::new().route(
+        "/openapi.json",
+        get(move || openapi::get_openapi(read_only)),
+    )
+}
@@ -1,11 +1,17 @@
 use crate::server::extractors::TargetNodeExtractor;
-use crate::server::models::GetJsonParam;
+use crate::server::models::{
+    BatchImageBody, DuplicateParam, GetJsonParam, PageParam, SearchParam,
+};
+use crate::cache::SharedResponseCache;
+use crate::memory::SharedImageTracker;
+use crate::store::{get_pack_source, MountSkillCache, PackSources};
 use crate::{handlers, utils, Error, Result};
 
 use std::io::{BufWriter, Cursor};
 
-use axum::extract::{Path, Query};
-use axum::{body::Body, extract::State, http::header, response::IntoResponse};
+use axum::extract::{Json, Path, Query};
+use axum::{body::Body, extract::State, http::header, response::IntoResponse, Extension};
+use futures::StreamExt;
 use image::ImageFormat;
 use wz_reader::util::node_util;
 use wz_reader::WzNodeCast;
@@ -16,13 +22,8 @@ pub async fn get_ogg_sound(
     TargetNodeExtractor(node): TargetNodeExtractor,
 ) -> Result<impl IntoResponse> {
     // 1. 获取原始数据
-    let raw_sound_data = if let Some(raw) = node.read().unwrap().try_as_sound() {
-        // === 关键修改：移除了对 Binary 类型的强制检查 ===
-        // 只要是 Sound 节点，无论内部是 Binary(WAV) 还是 Mp3，我们都取出 buffer
-        raw.get_buffer()
-    } else {
-        return Err(Error::NodeTypeMismatch("WzSound"));
-    };
+    // 只要是 Sound 节点，无论内部是 Binary(WAV) 还是 Mp3，我们都取出 buffer
+    let raw_sound_data = handlers::resolve_sound_buffer(&node.read().unwrap())?;
 
     // 2. 调用通用的转换函数 (支持自动探测 WAV/MP3)
     let ogg_buffer = handlers::audio::convert_audio_to_ogg(&raw_sound_data)?;
@@ -59,12 +60,45 @@ pub async fn get_image(
     ))
 }
 
+/// all images in one binary container, see handlers::batch_image for the layout
+pub async fn batch_image(
+    State(root): State<AppState>,
+    Extension(images): Extension<SharedImageTracker>,
+    Json(body): Json<BatchImageBody>,
+) -> Result<impl IntoResponse> {
+    let container = tokio::task::spawn_blocking(move || {
+        let frames = match (&body.animation, &body.paths) {
+            (Some(animation), _) => handlers::resolve_batch_animation(&root.0, animation),
+            (None, Some(paths)) => Ok(handlers::resolve_batch_frames(&root.0, paths)),
+            (None, None) => Ok(Vec::new()),
+        };
+
+        // only the requested paths are parsed, the links are read without parsing
+        for path in body.animation.iter().chain(body.paths.iter().flatten()) {
+            images.touch(path);
+        }
+        images.enforce();
+
+        handlers::encode_batch_container(&frames?)
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream"),
+            (header::CACHE_CONTROL, "max-age=3600"),
+        ],
+        container,
+    ))
+}
+
 pub async fn get_image_unparsed(
     State(root): State<AppState>,
     Path(path): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let (image_node, path) =
-        node_util::get_image_node_from_path(&root.0, &path).ok_or(Error::NodeNotFound)?;
+    let (image_node, path) = node_util::get_image_node_from_path(&root.0, &path)
+        .ok_or_else(|| Error::node_not_found(&root.0.read().unwrap(), &path))?;
 
     let image = handlers::resolve_png_unparsed(&image_node, &path, Some(&root.0))?;
 
@@ -90,10 +124,9 @@ pub async fn get_raw(TargetNodeExtractor(node): TargetNodeExtractor) -> Result<i
 
     if let Some(raw) = node.read().unwrap().try_as_raw_data() {
         buffer = raw.get_buffer().to_vec();
-    } else if let Some(raw) = node.read().unwrap().try_as_sound() {
-        buffer = raw.get_buffer(); 
     } else {
-        return Err(Error::NodeTypeMismatch("WzRaw or WzSound"));
+        buffer = handlers::resolve_sound_buffer(&node.read().unwrap())
+            .map_err(|_| Error::NodeTypeMismatch("WzRaw or WzSound"))?;
     }
 
     Ok((
@@ -129,6 +162,8 @@ pub async fn get_json(
 
 pub async fn load_extra_paths(
     State(root): State<AppState>,
+    Extension(image_cache): Extension<SharedResponseCache>,
+    Extension(mount_skills): Extension<MountSkillCache>,
     Query(param): Query<std::collections::HashMap<String, String>>,
 ) -> Result<impl IntoResponse> {
     let empty_string = String::new();
@@ -141,7 +176,14 @@ pub async fn load_extra_paths(
             .collect::<Vec<&str>>()
     };
 
-    utils::load_wz_by_base(root.0, &pathes, None, None).await?;
+    let reporter = utils::LoadReporter::silent(&pathes.join(","));
+
+    utils::load_wz_by_base(root.0, &pathes, None, None, reporter).await?;
+
+    // the loaded folders replace the nodes the cached images came from
+    image_cache.clear();
+    // and may bring the skills of more mounts
+    *mount_skills.write().unwrap() = None;
 
     Ok(())
 }
@@ -155,7 +197,7 @@ pub async fn parse(
     let _ = node_read
         .at_path(&path)
         .map(|n| node_util::parse_node(&n))
-        .ok_or(Error::NodeNotFound)?;
+        .ok_or_else(|| Error::node_not_found(&node_read, &path))?;
 
     Ok(())
 }
@@ -168,7 +210,169 @@ pub async fn unparse(
 
     node.at_path(&path)
         .map(|n| n.write().unwrap().unparse())
-        .ok_or(Error::NodeNotFound)?;
+        .ok_or_else(|| Error::node_not_found(&node, &path))?;
 
     Ok(())
 }
+
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchPage {
+    results: Vec<handlers::SearchResult>,
+    /// the offset of next page, None if there is no more result
+    next_offset: Option<usize>,
+}
+
+pub async fn search(
+    State(root): State<AppState>,
+    Extension(images): Extension<SharedImageTracker>,
+    Query(param): Query<SearchParam>,
+) -> Result<impl IntoResponse> {
+    let root_path = param.root.unwrap_or_default();
+    let root_path = root_path.trim_matches('/').to_string();
+
+    let search_root = if root_path.is_empty() {
+        root.0.clone()
+    } else {
+        handlers::path::get_node_parsed(&root.0, &root_path)?
+    };
+
+    let options = handlers::SearchOptions {
+        name: param
+            .name
+            .map(|name| handlers::build_name_matcher(&name, param.regex.unwrap_or(false)))
+            .transpose()?,
+        types: param._type.map(|types| {
+            types
+                .split(',')
+                .map(|t| t.trim().to_lowercase())
+                .filter(|t| !t.is_empty())
+                .collect()
+        }),
+        value: param.value,
+        max_depth: param.depth,
+        parse_images: param.parse.unwrap_or(false),
+        images: Some(images),
+    };
+
+    if param.stream.unwrap_or(false) {
+        let (sender, receiver) = futures::channel::mpsc::unbounded::<String>();
+
+        tokio::task::spawn_blocking(move || {
+            // stop searching once the client is gone
+            let _ = handlers::search_nodes(&search_root, &root_path, &options, &mut |result| {
+                let line = serde_json::to_string(&result).unwrap_or_default() + "\n";
+                sender.unbounded_send(line).is_ok()
+            });
+        });
+
+        let stream = receiver.map(Ok::<_, std::convert::Infallible>);
+
+        return Ok((
+            [(header::CONTENT_TYPE, "application/x-ndjson")],
+            Body::from_stream(stream),
+        )
+            .into_response());
+    }
+
+    let offset = param.offset.unwrap_or(0);
+    // an empty page would give the same `nextOffset` forever
+    let limit = param.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).max(1);
+
+    let page = tokio::task::spawn_blocking(move || {
+        let mut skipped = 0;
+        let mut results = Vec::new();
+        let mut has_more = false;
+
+        handlers::search_nodes(&search_root, &root_path, &options, &mut |result| {
+            if skipped < offset {
+                skipped += 1;
+                return true;
+            }
+            if results.len() == limit {
+                has_more = true;
+                return false;
+            }
+            results.push(result);
+            true
+        })?;
+
+        Ok::<_, Error>(SearchPage {
+            next_offset: has_more.then_some(offset + results.len()),
+            results,
+        })
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/json")],
+        serde_json::to_string(&page)?,
+    )
+        .into_response())
+}
+
+/// the children of the node in natural order, the image is parsed to list its children
+pub async fn get_children(
+    State(root): State<AppState>,
+    Extension(pack_sources): Extension<PackSources>,
+    path: Option<Path<String>>,
+    Query(param): Query<PageParam>,
+) -> Result<impl IntoResponse> {
+    let path = path.map(|Path(path)| path).unwrap_or_default();
+    let path = path.trim_matches('/');
+
+    let node = if path.is_empty() {
+        root.0.clone()
+    } else {
+        handlers::path::get_node_parsed(&root.0, path)?
+    };
+
+    node_util::parse_node(&node)?;
+
+    let page = handlers::resolve_children_info(
+        &node.read().unwrap(),
+        path,
+        param.offset.unwrap_or(0),
+        param.limit,
+        |child_path| get_pack_source(&pack_sources, child_path),
+    )?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/json")],
+        serde_json::to_string(&page)?,
+    ))
+}
+
+/// clusters of duplicate and near duplicate png under the root, it may take a while
+pub async fn get_duplicates(
+    State(root): State<AppState>,
+    Extension(images): Extension<SharedImageTracker>,
+    Query(param): Query<DuplicateParam>,
+) -> Result<impl IntoResponse> {
+    let default_options = handlers::DuplicateOptions::default();
+    let options = handlers::DuplicateOptions {
+        threshold: param.threshold.unwrap_or(default_options.threshold),
+        min_pixels: param.min_pixels.unwrap_or(default_options.min_pixels),
+    };
+
+    let report = tokio::task::spawn_blocking(move || {
+        let path = param.root.trim_matches('/');
+        let node = handlers::path::get_node_parsed(&root.0, path)?;
+        let report = handlers::resolve_duplicate_sprites(&node, path, Some(&root.0), &options);
+
+        images.track_parsed(&node);
+        images.enforce();
+
+        report
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/json")],
+        serde_json::to_string(&report)?,
+    ))
+}
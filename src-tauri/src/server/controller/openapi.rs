@@ -0,0 +1,10 @@
+use axum::{http::header, response::IntoResponse};
+
+use crate::server::openapi;
+
+pub async fn get_openapi(read_only: bool) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        openapi::resolve_openapi_document(read_only).to_string(),
+    )
+}
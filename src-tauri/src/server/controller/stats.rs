@@ -0,0 +1,30 @@
+use axum::{extract::State, http::header, response::IntoResponse, Extension};
+use tokio::task::spawn_blocking;
+
+use crate::cache::SharedResponseCache;
+use crate::memory::SharedImageTracker;
+use crate::stats::{self, SharedRuntimeStats};
+use crate::{Error, Result};
+
+use super::super::AppState;
+
+pub async fn get_stats(
+    State((root, string_dict)): State<AppState>,
+    Extension(images): Extension<SharedImageTracker>,
+    Extension(image_cache): Extension<SharedResponseCache>,
+    Extension(runtime): Extension<SharedRuntimeStats>,
+) -> Result<impl IntoResponse> {
+    let report = spawn_blocking(move || {
+        stats::resolve_stats(&root, &string_dict, &images, &image_cache, &runtime)
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json"),
+            (header::CACHE_CONTROL, "no-store"),
+        ],
+        serde_json::to_string(&report)?,
+    ))
+}
@@ -2,17 +2,22 @@ use axum::{
     extract::{Query, State},
     http::header,
     response::IntoResponse,
+    Extension, Json,
 };
 use serde_json::Value;
+use tokio::task::spawn_blocking;
 use wz_reader::util::node_util;
 
-use crate::{handlers, Error, Result};
+use crate::memory::SharedImageTracker;
+use crate::stats::{SharedRuntimeStats, EQUIP_CATALOG_CACHE};
+use crate::{handlers, Error, MountSkillCache, MountSkillOverrides, Result};
 
 use super::super::models::GetEquipListParam;
 use super::super::AppState;
 
 pub async fn prepare_equip(
     State((root, string_dict)): State<AppState>,
+    Extension(stats): Extension<SharedRuntimeStats>,
     Query(GetEquipListParam { extra }): Query<GetEquipListParam>,
 ) -> Result<impl IntoResponse> {
     let equip_string_node = handlers::get_equip_string(&root)?;
@@ -31,13 +36,14 @@ pub async fn prepare_equip(
         }
     }
 
-    let string_node = equip_string_node
-        .read()
-        .unwrap()
-        .at("Eqp")
-        .ok_or(Error::NodeNotFound)?;
+    let string_node = equip_string_node.read().unwrap().at("Eqp");
+    let string_node = string_node.ok_or_else(|| {
+        let path = format!("{}/Eqp", handlers::path::EQUIP_STRING_PATH);
+        Error::node_not_found(&root.read().unwrap(), &path)
+    })?;
 
     if let Ok(ref mut string_read) = string_dict.write() {
+        stats.record_cache(EQUIP_CATALOG_CACHE, string_read.len() != 0);
         if string_read.len() == 0 {
             string_read.extend(handlers::resolve_equip_string(
                 &root,
@@ -94,8 +100,21 @@ pub async fn get_chairs(State((root, _)): State<AppState>) -> Result<impl IntoRe
     ))
 }
 
-pub async fn get_mounts(State((root, _)): State<AppState>) -> Result<impl IntoResponse> {
-    let result = handlers::resolve_mount_string(&root)?;
+pub async fn get_mounts(
+    State((root, _)): State<AppState>,
+    Extension(mount_skills): Extension<MountSkillCache>,
+    Extension(mount_overrides): Extension<MountSkillOverrides>,
+    Extension(images): Extension<SharedImageTracker>,
+) -> Result<impl IntoResponse> {
+    // the first call scans every skill image
+    let result = spawn_blocking(move || {
+        let overrides = mount_overrides.read().unwrap().clone();
+        let mount_skill_map =
+            handlers::resolve_mount_skill_map(&root, &mount_skills, &overrides, &images);
+        handlers::resolve_mount_string(&root, &mount_skill_map)
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
 
     let result = result
         .iter()
@@ -113,6 +132,26 @@ pub async fn get_mounts(State((root, _)): State<AppState>) -> Result<impl IntoRe
     ))
 }
 
+/// the mounts still without skill, to fill the overrides file
+pub async fn get_mount_report(
+    State((root, _)): State<AppState>,
+    Extension(mount_skills): Extension<MountSkillCache>,
+    Extension(mount_overrides): Extension<MountSkillOverrides>,
+    Extension(images): Extension<SharedImageTracker>,
+) -> Result<impl IntoResponse> {
+    // the first call scans every skill image
+    let report = spawn_blocking(move || {
+        let overrides = mount_overrides.read().unwrap().clone();
+        let mount_skill_map =
+            handlers::resolve_mount_skill_map(&root, &mount_skills, &overrides, &images);
+        handlers::resolve_mount_skill_report(&root, &mount_skill_map)
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))??;
+
+    Ok(Json(report))
+}
+
 pub async fn get_skills(State((root, _)): State<AppState>) -> Result<impl IntoResponse> {
     let result = handlers::resolve_skill_string(&root)?;
 
@@ -36,11 +36,12 @@ where
 
         let target = if force_parse {
             root.at_path_parsed(&path).map_err(|e| match e {
-                node::Error::NodeNotFound => Error::NodeNotFound,
+                node::Error::NodeNotFound => Error::node_not_found(&root, &path),
                 _ => Error::NodeError(e),
             })
         } else {
-            root.at_path(&path).ok_or(Error::NodeNotFound)
+            root.at_path(&path)
+                .ok_or_else(|| Error::node_not_found(&root, &path))
         };
 
         let target = target.map_err(IntoResponse::into_response)?;
@@ -81,11 +82,12 @@ where
 
         let target = if force_parse {
             root.at_path_parsed(&path).map_err(|e| match e {
-                node::Error::NodeNotFound => Error::NodeNotFound,
+                node::Error::NodeNotFound => Error::node_not_found(&root, &path),
                 _ => Error::NodeError(e),
             })
         } else {
-            root.at_path(&path).ok_or(Error::NodeNotFound)
+            root.at_path(&path)
+                .ok_or_else(|| Error::node_not_found(&root, &path))
         };
 
         Ok(TargetNodeOptionExtractor(target.ok()))
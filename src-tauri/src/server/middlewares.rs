@@ -1,12 +1,22 @@
+use std::collections::HashMap;
+
+use std::time::Instant;
+
+use crate::cache::{get_content_etag, CachedResponse, SharedResponseCache};
+use crate::memory::SharedImageTracker;
+use crate::stats::{SharedRuntimeStats, IMAGE_RESPONSE_CACHE, PARSED_IMAGE_CACHE};
 use crate::Error;
 use axum::{
-    extract::{Query, Request, State},
-    http::{header, StatusCode},
+    body::{to_bytes, Body},
+    extract::{MatchedPath, Path, Query, Request, State},
+    Extension,
+    http::{header, HeaderValue, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
 use wz_reader::{WzNodeArc, WzNodeCast};
 
+use super::access::SharedServerAccess;
 use super::models::GetJsonParam;
 
 pub async fn root_check_middleware(
@@ -24,6 +34,18 @@ pub async fn root_check_middleware(
     next.run(req).await
 }
 
+pub async fn token_middleware(
+    State(access): State<SharedServerAccess>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !access.is_authorized(&req) {
+        return Error::Unauthorized.into_response();
+    }
+
+    next.run(req).await
+}
+
 // Read cache value from query and apply in header
 pub async fn cache_control_from_query_middleware(
     Query(query): Query<GetJsonParam>,
@@ -49,3 +71,126 @@ pub async fn cache_control_from_query_middleware(
 
     res
 }
+
+/// pin the image of the requested path while the request is running, then unparse
+/// the least recently used images if over the memory budget
+pub async fn memory_budget_middleware(
+    State(images): State<SharedImageTracker>,
+    Extension(stats): Extension<SharedRuntimeStats>,
+    params: Option<Path<HashMap<String, String>>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(path) = params.and_then(|Path(mut params)| params.remove("path")) else {
+        return next.run(req).await;
+    };
+
+    let res = {
+        let _pin = images.pin(&path);
+        next.run(req).await
+    };
+
+    tokio::task::spawn_blocking(move || {
+        if let Some(is_hit) = images.touch(&path) {
+            stats.record_cache(PARSED_IMAGE_CACHE, is_hit);
+        }
+        images.enforce();
+    });
+
+    res
+}
+
+/// count the requests and latencies by the matched route
+pub async fn request_stats_middleware(
+    State(stats): State<SharedRuntimeStats>,
+    matched_path: Option<MatchedPath>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let route = matched_path
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let started = Instant::now();
+
+    let res = next.run(req).await;
+
+    let is_error = res.status().is_client_error() || res.status().is_server_error();
+    stats.record_request(&route, started.elapsed(), is_error);
+
+    res
+}
+
+fn is_etag_matched(if_none_match: Option<&HeaderValue>, etag: &HeaderValue) -> bool {
+    if_none_match
+        .and_then(|v| v.to_str().ok())
+        .map_or(false, |v| {
+            v.split(',')
+                .any(|tag| tag.trim() == "*" || tag.trim().as_bytes() == etag.as_bytes())
+        })
+}
+
+fn not_modified(cached: &CachedResponse) -> Response {
+    let mut res = StatusCode::NOT_MODIFIED.into_response();
+    res.headers_mut().insert(header::ETAG, cached.etag.clone());
+    if let Some(cache_control) = cached.headers.get(header::CACHE_CONTROL) {
+        res.headers_mut().insert(header::CACHE_CONTROL, cache_control.clone());
+    }
+    res
+}
+
+/// cache the encoded image by path and query, answer `If-None-Match` with 304
+pub async fn image_cache_middleware(
+    Extension(cache): Extension<SharedResponseCache>,
+    Extension(stats): Extension<SharedRuntimeStats>,
+    req: Request,
+    next: Next,
+) -> Response {
+    // the path includes the route, so the format is part of the key
+    let key = req
+        .uri()
+        .path_and_query()
+        .map(|v| v.as_str().to_string())
+        .unwrap_or_default();
+
+    if let Some(cached) = cache.get(&key) {
+        stats.record_cache(IMAGE_RESPONSE_CACHE, true);
+
+        if is_etag_matched(req.headers().get(header::IF_NONE_MATCH), &cached.etag) {
+            return not_modified(&cached);
+        }
+
+        let mut res = Response::new(Body::from(cached.body));
+        *res.headers_mut() = cached.headers;
+        res.headers_mut().insert(header::ETAG, cached.etag);
+        return res;
+    }
+
+    stats.record_cache(IMAGE_RESPONSE_CACHE, false);
+
+    let if_none_match = req.headers().get(header::IF_NONE_MATCH).cloned();
+    let res = next.run(req).await;
+
+    if res.status() != StatusCode::OK {
+        return res;
+    }
+
+    let (mut parts, body) = res.into_parts();
+    let Ok(body) = to_bytes(body, usize::MAX).await else {
+        return Error::ImageSendError.into_response();
+    };
+
+    let etag = get_content_etag(&body);
+    let cached = CachedResponse {
+        etag: etag.clone(),
+        headers: parts.headers.clone(),
+        body: body.clone(),
+    };
+    cache.insert(key, cached.clone());
+
+    if is_etag_matched(if_none_match.as_ref(), &etag) {
+        return not_modified(&cached);
+    }
+
+    parts.headers.insert(header::ETAG, etag);
+    Response::from_parts(parts, Body::from(body))
+}
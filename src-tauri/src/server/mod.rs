@@ -1,44 +1,139 @@
+pub mod access;
+pub mod config;
 pub mod controller;
 pub mod extractors;
 pub mod middlewares;
 pub mod models;
+pub mod openapi;
 
-use crate::{store::StringDict, Error};
+use access::SharedServerAccess;
+
+use crate::{
+    cache::SharedResponseCache, link_index::SharedLinkIndex, memory::SharedImageTracker,
+    stats::SharedRuntimeStats,
+    store::{MountSkillCache, MountSkillOverrides, PackSources, StringDict},
+    Error,
+};
 
 use axum::{
-    http::StatusCode, 
+    http::StatusCode,
     response::{IntoResponse, Response},
     routing::get,
-    serve, Router,
+    serve, Extension, Json, Router,
 };
-use tower_http::cors::{CorsLayer, Any}; 
-use wz_reader::WzNodeArc;
+use serde::Serialize;
+use serde_json::Value;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use wz_reader::{node, WzNodeArc};
 
 pub type AppState = (WzNodeArc, StringDict);
 
-pub async fn app(node: WzNodeArc, string_dict: StringDict, port: u16) -> crate::Result<()> {
+pub const DEFAULT_PORT: u16 = 12258;
+
+/// where the server listens and which routes it serves
+pub struct ServerOptions {
+    pub host: String,
+    pub port: u16,
+    /// skip the routes change the loaded tree, like `/node/unparse` and `/node/load_extra_paths`
+    pub read_only: bool,
+}
+
+impl ServerOptions {
+    /// the server of the desktop app, only reachable from this machine
+    pub fn local(port: u16) -> Self {
+        ServerOptions {
+            host: "127.0.0.1".to_string(),
+            port,
+            read_only: false,
+        }
+    }
+}
+
+/// the shared state of the server, handed to the routes as the state and extensions
+#[derive(Clone)]
+pub struct ServerState {
+    pub node: WzNodeArc,
+    pub string_dict: StringDict,
+    pub images: SharedImageTracker,
+    pub stats: SharedRuntimeStats,
+    pub image_cache: SharedResponseCache,
+    pub pack_sources: PackSources,
+    pub link_index: SharedLinkIndex,
+    pub access: SharedServerAccess,
+    pub mount_overrides: MountSkillOverrides,
+    pub mount_skills: MountSkillCache,
+}
+
+/// every route of the server with its middlewares
+pub fn router(state: ServerState, options: &ServerOptions) -> Router {
+    let ServerState {
+        node,
+        string_dict,
+        images,
+        stats,
+        image_cache,
+        pack_sources,
+        link_index,
+        access,
+        mount_overrides,
+        mount_skills,
+    } = state;
+
     let layer_state = node.clone();
-    let app = Router::new()
+    let cors_access = access.clone();
+    Router::new()
         .route("/", get(hello))
         .nest("/mapping", controller::mapping_router())
-        .nest("/node", controller::node_router())
+        .nest("/node", controller::node_router(options.read_only))
         .nest("/string", controller::string_router())
+        .nest("/export", controller::export_router())
+        .nest("/links", controller::links_router())
+        .route_layer(axum::middleware::from_fn_with_state(
+            images.clone(),
+            middlewares::memory_budget_middleware,
+        ))
         .route_layer(axum::middleware::from_fn_with_state(
             layer_state,
             middlewares::root_check_middleware,
         ))
+        // stats and openapi are available before the root initialized
+        .merge(controller::stats_router())
+        .merge(controller::openapi_router(options.read_only))
+        .route_layer(axum::middleware::from_fn_with_state(
+            stats.clone(),
+            middlewares::request_stats_middleware,
+        ))
         .route_layer(axum::middleware::from_fn(
             middlewares::cache_control_from_query_middleware,
         ))
+        // every route requires the session token, the preflight is answered by cors
+        .route_layer(axum::middleware::from_fn_with_state(
+            access,
+            middlewares::token_middleware,
+        ))
         .route_layer(
             CorsLayer::new()
-                .allow_origin(Any)   // 允许任何来源 (解决 8041 端口问题)
-                .allow_methods(Any)  // 允许任何方法 (GET, POST, OPTIONS 等)
-                .allow_headers(Any), // 允许任何 Header (非常关键！解决 fetch 失败的核心)
+                // only the webview and the configured origins can read the response
+                .allow_origin(AllowOrigin::predicate(move |origin, _| {
+                    cors_access.is_origin_allowed(origin)
+                }))
+                .allow_methods(Any)
+                .allow_headers(Any),
         )
-        .with_state((node, string_dict));
+        .layer(Extension(images))
+        .layer(Extension(image_cache))
+        .layer(Extension(pack_sources))
+        .layer(Extension(mount_overrides))
+        .layer(Extension(mount_skills))
+        .layer(Extension(link_index))
+        .layer(Extension(stats))
+        .with_state((node, string_dict))
+}
 
-    let host = format!("127.0.0.1:{port}");
+pub async fn app(state: ServerState, options: ServerOptions) -> crate::Result<()> {
+    let app = router(state, &options);
+
+    let host = format!("{}:{}", options.host, options.port);
 
     println!("You enable the axum-server feature, Listening on http://{host}");
 
@@ -51,38 +146,67 @@ async fn hello() -> &'static str {
     "Hi"
 }
 
+/// the json body of every error response
+#[derive(Serialize)]
+pub struct ErrorBody {
+    /// stable code of the error, see `Error::get_code`
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+fn get_status_code(error: &Error) -> StatusCode {
+    match error {
+        // 归类为 500 Internal Server Error 的错误
+        Error::Io(_)
+        | Error::JsonParseError(_)
+        | Error::ImageParseError(_)
+        | Error::SoundParseError(_)
+        | Error::StringParseError(_)
+        | Error::ZipError(_)
+        | Error::XmlError(_)
+        | Error::ImageSendError
+        | Error::ImageProcessingError(_)
+        | Error::AudioProcessingError(_)
+        | Error::ExportError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+
+        // 归类为 400 Bad Request 的错误
+        Error::InitWzFailed
+        | Error::InvalidWzKey(_)
+        | Error::InvalidSearchPattern(_)
+        | Error::InvalidSkillId(_)
+        | Error::InvalidFrameContainer(_)
+        | Error::NodeTypeMismatch(_) => StatusCode::BAD_REQUEST,
+        Error::NodeError(node::Error::NodeNotFound) => StatusCode::NOT_FOUND,
+        Error::NodeError(_) => StatusCode::BAD_REQUEST,
+
+        // 归类为 401 Unauthorized 的错误
+        Error::Unauthorized => StatusCode::UNAUTHORIZED,
+
+        // 归类为 403 Forbidden 的错误
+        Error::NotInitialized => StatusCode::FORBIDDEN,
+
+        // 归类为 404 Not Found 的错误
+        Error::NodeNotFound { .. } | Error::JobNotFound => StatusCode::NOT_FOUND,
+
+        // 移动端特有错误
+        #[cfg(all(mobile, feature = "desktop"))]
+        Error::PluginInvoke(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
-        match self {
-            // 归类为 500 Internal Server Error 的错误
-            Error::Io(_)
-            | Error::JsonParseError(_)
-            | Error::ImageParseError(_)
-            | Error::SoundParseError(_)       // <--- 确保包含
-            | Error::StringParseError(_)
-            | Error::ImageSendError
-            | Error::ImageProcessingError(_)  // <--- 确保包含
-            | Error::AudioProcessingError(_)  // <--- 确保包含
-            => {
-                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
-            }
-
-            // 归类为 400 Bad Request 的错误
-            Error::InitWzFailed => (StatusCode::BAD_REQUEST, self.to_string()).into_response(),
-            Error::NodeError(_) => (StatusCode::BAD_REQUEST, self.to_string()).into_response(),
-            Error::NodeTypeMismatch(_) => {
-                (StatusCode::BAD_REQUEST, self.to_string()).into_response()
-            }
-
-            // 归类为 403 Forbidden 的错误
-            Error::NotInitialized => (StatusCode::FORBIDDEN, self.to_string()).into_response(),
-
-            // 归类为 404 Not Found 的错误
-            Error::NodeNotFound => (StatusCode::NOT_FOUND, self.to_string()).into_response(),
-
-            // 移动端特有错误
-            #[cfg(mobile)]
-            Error::PluginInvoke(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response(),
-        }
+        let body = ErrorBody {
+            code: self.get_code(),
+            message: self.to_string(),
+            path: self.get_path().map(String::from),
+            details: self.get_details(),
+        };
+
+        (get_status_code(&self), Json(body)).into_response()
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,6 @@
-use serde::Deserialize;
+use serde::{de, Deserialize, Deserializer};
+
+use crate::handlers::AnimationFormat;
 
 /// A universal query parameter for getting json response
 #[derive(Deserialize)]
@@ -19,3 +21,92 @@ pub struct GetJsonParam {
 pub struct GetEquipListParam {
     pub extra: Option<bool>,
 }
+
+#[derive(Deserialize)]
+pub struct GetXmlParam {
+    /// write canvas and sound as base64 in the xml instead of side files
+    pub inline: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct SkillTimelineParam {
+    /// when the hit and mob start, default to the end of the ball
+    pub hit_delay: Option<i32>,
+    /// where the target stands relative to the character
+    pub target_x: Option<i32>,
+    pub target_y: Option<i32>,
+    /// gif only, see RenderAnimationParam
+    pub alpha_threshold: Option<u8>,
+    #[serde(default, deserialize_with = "deserialize_hex_color")]
+    pub matte: Option<[u8; 3]>,
+}
+
+#[derive(Deserialize)]
+pub struct RenderAnimationParam {
+    /// samples per second of the alpha and zoom ramps
+    pub fps: Option<u32>,
+    pub format: Option<AnimationFormat>,
+    /// gif only, pixels with alpha below this are transparent
+    pub alpha_threshold: Option<u8>,
+    /// gif only, the color to blend the semi transparent pixels over, like `ffffff`
+    #[serde(default, deserialize_with = "deserialize_hex_color")]
+    pub matte: Option<[u8; 3]>,
+}
+
+/// either a list of node paths or one animation node path
+#[derive(Deserialize)]
+pub struct BatchImageBody {
+    pub paths: Option<Vec<String>>,
+    pub animation: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SearchParam {
+    /// the path to search under, empty for the whole tree
+    pub root: Option<String>,
+    /// glob like `ball*`, or regex when `regex` is true
+    pub name: Option<String>,
+    pub regex: Option<bool>,
+    /// comma separated type names, like `png,uol`
+    #[serde(rename = "type")]
+    pub _type: Option<String>,
+    pub value: Option<String>,
+    pub depth: Option<usize>,
+    /// parse the unparsed images along the way, default false
+    pub parse: Option<bool>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+    /// stream every result as a json line instead of a page
+    pub stream: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct PageParam {
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct DuplicateParam {
+    /// the path to scan under
+    pub root: String,
+    /// max dhash distance of near duplicate, up to 3
+    pub threshold: Option<u32>,
+    pub min_pixels: Option<u32>,
+}
+
+/// `ffffff` or `#ffffff`
+fn deserialize_hex_color<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<[u8; 3]>, D::Error> {
+    let Some(text) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    let hex = text.trim_start_matches('#');
+    let value = u32::from_str_radix(hex, 16)
+        .ok()
+        .filter(|_| hex.len() == 6)
+        .ok_or_else(|| de::Error::custom(format!("invalid color: {}", text)))?;
+
+    Ok(Some([(value >> 16) as u8, (value >> 8) as u8, value as u8]))
+}
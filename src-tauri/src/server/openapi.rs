@@ -0,0 +1,938 @@
+use serde_json::{json, Map, Value};
+
+use super::access::{TOKEN_HEADER, TOKEN_QUERY};
+
+// the description of every route in controller/mod.rs, keep it in sync when a route,
+// a query struct in server/models.rs or a response struct is changed
+
+pub const OPENAPI_VERSION: &str = "3.1.0";
+
+/// the routes `node_router` skips in read only mode
+const TREE_CHANGING_PATHS: &[&str] = &["/node/unparse/{path}", "/node/load_extra_paths"];
+
+#[derive(Clone)]
+enum ParamIn {
+    Path,
+    Query,
+}
+
+#[derive(Clone)]
+struct ApiParam {
+    name: &'static str,
+    location: ParamIn,
+    schema: Value,
+    description: &'static str,
+    required: bool,
+}
+
+struct ApiRoute {
+    method: &'static str,
+    path: &'static str,
+    summary: &'static str,
+    params: Vec<ApiParam>,
+    body: Option<Value>,
+    response: ApiResponse,
+}
+
+enum ApiResponse {
+    /// an application/json body of the schema
+    Json(Value),
+    /// a binary body of the content type
+    Binary(&'static str),
+    /// a text/plain body
+    Text,
+    /// 200 with an empty body
+    Empty,
+}
+
+fn query(name: &'static str, schema: Value, description: &'static str) -> ApiParam {
+    ApiParam {
+        name,
+        location: ParamIn::Query,
+        schema,
+        description,
+        required: false,
+    }
+}
+
+fn required_query(name: &'static str, schema: Value, description: &'static str) -> ApiParam {
+    ApiParam {
+        required: true,
+        ..query(name, schema, description)
+    }
+}
+
+fn path_param(name: &'static str, description: &'static str) -> ApiParam {
+    ApiParam {
+        name,
+        location: ParamIn::Path,
+        schema: json!({ "type": "string" }),
+        description,
+        required: true,
+    }
+}
+
+fn node_path() -> ApiParam {
+    path_param("path", "slash separated node path, like `Character/00002000.img/stand1/0`")
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn array_of(item: Value) -> Value {
+    json!({ "type": "array", "items": item })
+}
+
+/// a fixed length array, serialized from a rust tuple
+fn tuple_of(items: &[(&str, Value)]) -> Value {
+    let names = items.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+
+    json!({
+        "type": "array",
+        "prefixItems": items.iter().map(|(_, schema)| schema.clone()).collect::<Vec<_>>(),
+        "items": false,
+        "minItems": items.len(),
+        "maxItems": items.len(),
+        "description": format!("[{}]", names.join(", ")),
+    })
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+fn boolean() -> Value {
+    json!({ "type": "boolean" })
+}
+
+fn integer() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn number() -> Value {
+    json!({ "type": "number" })
+}
+
+/// server::models::GetJsonParam, used by every route resolves the node by TargetNodeExtractor
+fn get_json_params() -> Vec<ApiParam> {
+    vec![
+        query("simple", boolean(), "simplified version of json response"),
+        query("force_parse", boolean(), "parse the images along the path"),
+        query("sort", boolean(), "sort the json response"),
+        query("resolve_uol", boolean(), "follow the uol to the target node"),
+        query("cache", integer(), "max-age of the cache-control header in seconds"),
+    ]
+}
+
+fn target_node_params() -> Vec<ApiParam> {
+    let mut params = vec![node_path()];
+    params.extend(get_json_params());
+    params
+}
+
+/// server::models::PageParam
+fn page_params() -> Vec<ApiParam> {
+    vec![
+        query("offset", integer(), "skip the first n items"),
+        query("limit", integer(), "max items of the page, no limit if absent"),
+    ]
+}
+
+/// server::models::SearchParam
+fn search_params() -> Vec<ApiParam> {
+    vec![
+        query("root", string(), "the path to search under, empty for the whole tree"),
+        query("name", string(), "glob like `ball*`, or regex when `regex` is true"),
+        query("regex", boolean(), "treat `name` as a regex"),
+        query("type", string(), "comma separated type names, like `png,uol`"),
+        query("value", string(), "number equality or case insensitive substring of string"),
+        query("depth", integer(), "max depth relative to the search root"),
+        query("parse", boolean(), "parse the unparsed images along the way, default false"),
+        query("offset", integer(), "skip the first n results"),
+        query("limit", integer(), "max results of the page, default 100, at least 1"),
+        query("stream", boolean(), "stream every result as a json line instead of a page"),
+    ]
+}
+
+/// server::models::DuplicateParam
+fn duplicate_params() -> Vec<ApiParam> {
+    vec![
+        required_query("root", string(), "the path to scan under"),
+        query("threshold", integer(), "max dhash distance of near duplicate, up to 3"),
+        query("min_pixels", integer(), "skip the images smaller than this"),
+    ]
+}
+
+fn timeline_params() -> Vec<ApiParam> {
+    vec![
+        path_param("id", "skill id, like `2121005`"),
+        query("hit_delay", integer(), "when the hit and mob start, default to the end of the ball"),
+        query("target_x", integer(), "x of the target relative to the character"),
+        query("target_y", integer(), "y of the target relative to the character"),
+    ]
+}
+
+fn gif_params() -> Vec<ApiParam> {
+    vec![
+        query("alpha_threshold", integer(), "gif only, alpha below this is transparent"),
+        query("matte", string(), "gif only, the color behind the semi transparent pixels"),
+    ]
+}
+
+fn routes() -> Vec<ApiRoute> {
+    vec![
+        ApiRoute {
+            method: "get",
+            path: "/",
+            summary: "health check",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Text,
+        },
+        ApiRoute {
+            method: "get",
+            path: "/stats",
+            summary: "runtime statistics, available before the root initialized",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(schema_ref("StatsReport")),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/openapi.json",
+            summary: "this document",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(json!({ "type": "object" })),
+        },
+        // node
+        ApiRoute {
+            method: "get",
+            path: "/node/image/{path}",
+            summary: "the png node as webp",
+            params: target_node_params(),
+            body: None,
+            response: ApiResponse::Binary("image/webp"),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/node/image_unparsed/{path}",
+            summary: "the png node as webp without parsing the whole image",
+            params: vec![node_path()],
+            body: None,
+            response: ApiResponse::Binary("image/webp"),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/node/json/{path}",
+            summary: "the node and its children as json",
+            params: target_node_params(),
+            body: None,
+            response: ApiResponse::Json(json!({})),
+        },
+        ApiRoute {
+            method: "post",
+            path: "/node/batch_image",
+            summary: "decode many frames into one binary container, see handlers::batch_image",
+            params: vec![],
+            body: Some(schema_ref("BatchImageBody")),
+            response: ApiResponse::Binary("application/octet-stream"),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/node/search",
+            summary: "search nodes by name, type and value",
+            params: search_params(),
+            body: None,
+            response: ApiResponse::Json(schema_ref("SearchPage")),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/node/duplicates",
+            summary: "clusters of duplicate and near duplicate png",
+            params: duplicate_params(),
+            body: None,
+            response: ApiResponse::Json(schema_ref("DuplicateReport")),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/node/children",
+            summary: "the children of the root in natural order",
+            params: page_params(),
+            body: None,
+            response: ApiResponse::Json(schema_ref("NodeInfoPage")),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/node/children/{path}",
+            summary: "the children of the node in natural order",
+            params: [vec![node_path()], page_params()].concat(),
+            body: None,
+            response: ApiResponse::Json(schema_ref("NodeInfoPage")),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/node/raw/{path}",
+            summary: "the buffer of raw data or sound node",
+            params: target_node_params(),
+            body: None,
+            response: ApiResponse::Binary("audio/wav"),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/node/sound_ogg/{path}",
+            summary: "the sound node converted to ogg",
+            params: target_node_params(),
+            body: None,
+            response: ApiResponse::Binary("audio/ogg"),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/node/parse/{path}",
+            summary: "parse the node",
+            params: vec![node_path()],
+            body: None,
+            response: ApiResponse::Empty,
+        },
+        ApiRoute {
+            method: "get",
+            path: "/node/unparse/{path}",
+            summary: "release the parsed children of the node",
+            params: vec![node_path()],
+            body: None,
+            response: ApiResponse::Empty,
+        },
+        ApiRoute {
+            method: "get",
+            path: "/node/load_extra_paths",
+            summary: "load the extra wz files under the root",
+            params: vec![query("path", string(), "comma separated names of root children")],
+            body: None,
+            response: ApiResponse::Empty,
+        },
+        // mapping
+        ApiRoute {
+            method: "get",
+            path: "/mapping/smap",
+            summary: "slot name to slot description of smap.img",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(json!({
+                "type": "object",
+                "additionalProperties": string(),
+            })),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/mapping/zmap",
+            summary: "layer names of zmap.img from top to bottom",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(array_of(string())),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/mapping/images",
+            summary: "paths of every character, cash effect and nick tag image",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(array_of(string())),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/mapping/seteffect",
+            summary: "equip id to set effect id",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(json!({
+                "type": "object",
+                "additionalProperties": string(),
+            })),
+        },
+        // string
+        ApiRoute {
+            method: "get",
+            path: "/string/equip",
+            summary: "the equip catalog, call /string/equip/prepare first",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(array_of(schema_ref("EquipEntry"))),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/string/equip/prepare",
+            summary: "build the equip catalog",
+            params: vec![query("extra", boolean(), "also resolve cash, colorvar and effect")],
+            body: None,
+            response: ApiResponse::Empty,
+        },
+        ApiRoute {
+            method: "get",
+            path: "/string/chair",
+            summary: "the chair names",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(array_of(schema_ref("ChairEntry"))),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/string/mount",
+            summary: "the mount names",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(array_of(schema_ref("MountEntry"))),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/string/mount/report",
+            summary: "how many mounts have a skill, and the ones without",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(schema_ref("MountSkillReport")),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/string/skill",
+            summary: "the skill names",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(array_of(schema_ref("SkillEntry"))),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/string/map",
+            summary: "the map names",
+            params: vec![],
+            body: None,
+            response: ApiResponse::Json(array_of(schema_ref("MapEntry"))),
+        },
+        // export
+        ApiRoute {
+            method: "get",
+            path: "/export/skill/{id}",
+            summary: "the skill bundle as zip",
+            params: vec![path_param("id", "skill id, like `1001004`")],
+            body: None,
+            response: ApiResponse::Binary("application/zip"),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/export/skill_timeline/{id}",
+            summary: "the playback plan of every effect layer of the skill",
+            params: timeline_params(),
+            body: None,
+            response: ApiResponse::Json(schema_ref("SkillTimeline")),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/export/skill_timeline/{id}/{format}",
+            summary: "every effect layer of the skill composed into one animation",
+            params: [
+                timeline_params(),
+                vec![path_param("format", "`webp`, `apng` or `gif`")],
+                gif_params(),
+            ]
+            .concat(),
+            body: None,
+            response: ApiResponse::Binary("image/*"),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/export/animation/{path}",
+            summary: "the animation rendered with its alpha and zoom ramps, zigzag and move",
+            params: [
+                target_node_params(),
+                vec![
+                    query("fps", integer(), "samples per second of the ramps, default 30"),
+                    query("format", string(), "`webp` (default), `apng` or `gif`"),
+                ],
+                gif_params(),
+            ]
+            .concat(),
+            body: None,
+            response: ApiResponse::Binary("image/*"),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/export/godot/{path}",
+            summary: "the animation as a godot SpriteFrames .tres and its atlas png, in a zip",
+            params: target_node_params(),
+            body: None,
+            response: ApiResponse::Binary("application/zip"),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/export/unity/{path}",
+            summary: "the animation as an atlas png and a json of sprite rects and pivots, in a zip",
+            params: target_node_params(),
+            body: None,
+            response: ApiResponse::Binary("application/zip"),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/export/xml/{path}",
+            summary: "the node as xml, or a zip with side files when media is not inline",
+            params: [
+                target_node_params(),
+                vec![query("inline", boolean(), "write canvas and sound as base64 in the xml")],
+            ]
+            .concat(),
+            body: None,
+            response: ApiResponse::Binary("application/xml"),
+        },
+        // links
+        ApiRoute {
+            method: "get",
+            path: "/links/refs/{path}",
+            summary: "the nodes link to the path, only the indexed images are covered",
+            params: vec![node_path()],
+            body: None,
+            response: ApiResponse::Json(schema_ref("LinkReferences")),
+        },
+        ApiRoute {
+            method: "get",
+            path: "/links/index/{path}",
+            summary: "parse and index every image under the path",
+            params: vec![node_path()],
+            body: None,
+            response: ApiResponse::Json(json!({
+                "type": "object",
+                "properties": { "indexed": integer() },
+                "required": ["indexed"],
+            })),
+        },
+    ]
+}
+
+fn object(properties: Value, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn component_schemas() -> Value {
+    json!({
+        "ErrorBody": object(json!({
+            "code": {
+                "type": "string",
+                "description": "stable code like NODE_NOT_FOUND or CANVAS_DECODE_FAILED",
+            },
+            "message": string(),
+            "path": string(),
+            "details": {
+                "type": "object",
+                "description": "`resolved` and `missing` segment of NODE_NOT_FOUND, \
+                    or the underlying wz_reader error",
+            },
+        }), &["code", "message"]),
+        "NodeInfo": object(json!({
+            "type": string(),
+            "name": string(),
+            "hasChild": boolean(),
+            "pack": string(),
+            "value": { "description": "number, string or [x, y] of vector" },
+            "png": schema_ref("PngInfo"),
+            "sound": schema_ref("SoundInfo"),
+            "link": schema_ref("LinkInfo"),
+        }), &["type", "name", "hasChild"]),
+        "PngInfo": object(json!({
+            "width": integer(),
+            "height": integer(),
+            "format": integer(),
+        }), &["width", "height", "format"]),
+        "SoundInfo": object(json!({
+            "duration": integer(),
+            "codec": string(),
+        }), &["duration", "codec"]),
+        "LinkKind": { "type": "string", "enum": ["inlink", "outlink", "uol"] },
+        "LinkInfo": object(json!({
+            "kind": schema_ref("LinkKind"),
+            "target": string(),
+        }), &["kind", "target"]),
+        "NodeInfoPage": object(json!({
+            "total": integer(),
+            "offset": integer(),
+            "items": array_of(schema_ref("NodeInfo")),
+        }), &["total", "offset", "items"]),
+        "SearchResult": object(json!({
+            "path": string(),
+            "name": string(),
+            "type": string(),
+            "value": string(),
+        }), &["path", "name", "type"]),
+        "SearchPage": object(json!({
+            "results": array_of(schema_ref("SearchResult")),
+            "nextOffset": { "type": ["integer", "null"] },
+        }), &["results", "nextOffset"]),
+        "BatchImageBody": {
+            "type": "object",
+            "description": "either a list of node paths or one animation node path",
+            "properties": {
+                "paths": array_of(string()),
+                "animation": string(),
+            },
+        },
+        "DuplicateMember": object(json!({
+            "path": string(),
+            "width": integer(),
+            "height": integer(),
+            "distance": integer(),
+            "exact": boolean(),
+        }), &["path", "width", "height", "distance", "exact"]),
+        "DuplicateCluster": object(json!({
+            "representative": string(),
+            "members": array_of(schema_ref("DuplicateMember")),
+        }), &["representative", "members"]),
+        "DuplicateReport": object(json!({
+            "scanned": integer(),
+            "failed": integer(),
+            "clusters": array_of(schema_ref("DuplicateCluster")),
+        }), &["scanned", "failed", "clusters"]),
+        "LinkReference": object(json!({
+            "path": string(),
+            "kind": schema_ref("LinkKind"),
+        }), &["path", "kind"]),
+        "LinkReferences": object(json!({
+            "target": string(),
+            "references": array_of(schema_ref("LinkReference")),
+            "indexedImages": integer(),
+        }), &["target", "references", "indexedImages"]),
+        "StatsReport": object(json!({
+            "uptimeMs": integer(),
+            "files": array_of(object(json!({
+                "name": string(),
+                "path": string(),
+                "patchVersion": { "type": "integer" },
+            }), &["name", "path", "patchVersion"])),
+            "nodeCount": integer(),
+            "parsedImages": integer(),
+            "unparsedImages": integer(),
+            "memory": object(json!({
+                "parsedNodesBytes": integer(),
+                "catalogBytes": integer(),
+                "trackedImages": integer(),
+                "trackedImagesBytes": integer(),
+                "budgetBytes": integer(),
+                "imageCacheEntries": integer(),
+                "imageCacheBytes": integer(),
+            }), &[
+                "parsedNodesBytes",
+                "catalogBytes",
+                "trackedImages",
+                "trackedImagesBytes",
+                "budgetBytes",
+                "imageCacheEntries",
+                "imageCacheBytes",
+            ]),
+            "routes": array_of(object(json!({
+                "route": string(),
+                "count": integer(),
+                "errors": integer(),
+                "avgMs": number(),
+                "maxMs": number(),
+            }), &["route", "count", "errors", "avgMs", "maxMs"])),
+            "caches": array_of(object(json!({
+                "name": string(),
+                "hits": integer(),
+                "misses": integer(),
+                "hitRate": number(),
+            }), &["name", "hits", "misses", "hitRate"])),
+        }), &[
+            "uptimeMs",
+            "files",
+            "nodeCount",
+            "parsedImages",
+            "unparsedImages",
+            "memory",
+            "routes",
+            "caches",
+        ]),
+        "TimelineLayer": object(json!({
+            "name": string(),
+            "path": string(),
+            "kind": {
+                "type": "string",
+                "enum": ["effect", "special", "ball", "hit", "mob", "affected"],
+            },
+            "start": integer(),
+            "duration": integer(),
+            "repeat": boolean(),
+            "z": integer(),
+            "front": boolean(),
+            "offset": tuple_of(&[("x", integer()), ("y", integer())]),
+            "frames": array_of(object(json!({
+                "delay": integer(),
+                "origin": tuple_of(&[("x", integer()), ("y", integer())]),
+            }), &["delay", "origin"])),
+        }), &[
+            "name",
+            "path",
+            "kind",
+            "start",
+            "duration",
+            "repeat",
+            "z",
+            "front",
+            "offset",
+            "frames",
+        ]),
+        "SkillTimeline": object(json!({
+            "skillId": string(),
+            "path": string(),
+            "layers": array_of(schema_ref("TimelineLayer")),
+            "totalDuration": integer(),
+        }), &["skillId", "path", "layers", "totalDuration"]),
+        "EquipEntry": tuple_of(&[
+            ("category", string()),
+            ("id", string()),
+            ("name", string()),
+            ("cash", boolean()),
+            ("colorvar", boolean()),
+            ("effect", boolean()),
+        ]),
+        "ChairEntry": tuple_of(&[
+            ("id", string()),
+            ("folder", string()),
+            ("name", string()),
+        ]),
+        "MountEntry": tuple_of(&[("id", string()), ("name", string())]),
+        "MountSkillEntry": object(json!({
+            "id": string(),
+            "name": { "type": ["string", "null"] },
+            "skillId": { "type": ["string", "null"] },
+            "skillName": { "type": ["string", "null"] },
+            "source": {
+                "type": ["string", "null"],
+                "enum": ["skill", "ridingSkillInfo", "override", null],
+            },
+        }), &["id", "name", "skillId", "skillName", "source"]),
+        "MountSkillReport": object(json!({
+            "total": integer(),
+            "mapped": integer(),
+            "overridden": integer(),
+            "unmapped": array_of(schema_ref("MountSkillEntry")),
+        }), &["total", "mapped", "overridden", "unmapped"]),
+        "SkillEntry": tuple_of(&[
+            ("id", string()),
+            ("folder", string()),
+            ("name", string()),
+        ]),
+        "MapEntry": tuple_of(&[
+            ("id", string()),
+            ("name", string()),
+            ("streetName", string()),
+        ]),
+    })
+}
+
+fn param_to_json(param: &ApiParam) -> Value {
+    json!({
+        "name": param.name,
+        "in": match param.location {
+            ParamIn::Path => "path",
+            ParamIn::Query => "query",
+        },
+        "required": param.required,
+        "description": param.description,
+        "schema": param.schema,
+    })
+}
+
+fn error_response(description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema_ref("ErrorBody") } },
+    })
+}
+
+fn route_to_operation(route: &ApiRoute) -> Value {
+    let ok = match &route.response {
+        ApiResponse::Json(schema) => json!({
+            "description": "ok",
+            "content": { "application/json": { "schema": schema } },
+        }),
+        ApiResponse::Binary(content_type) => json!({
+            "description": "ok",
+            "content": { *content_type: { "schema": { "type": "string", "format": "binary" } } },
+        }),
+        ApiResponse::Text => json!({
+            "description": "ok",
+            "content": { "text/plain": { "schema": string() } },
+        }),
+        ApiResponse::Empty => json!({ "description": "ok" }),
+    };
+
+    let mut operation = json!({
+        "summary": route.summary,
+        "parameters": route.params.iter().map(param_to_json).collect::<Vec<_>>(),
+        "responses": {
+            "200": ok,
+            "400": error_response("invalid node, pattern or parameter"),
+            "401": error_response("missing or invalid access token"),
+            "403": error_response("root wz not yet initialized"),
+            "404": error_response("node not found"),
+            "500": error_response("failed to process the node"),
+        },
+    });
+
+    if let Some(body) = &route.body {
+        operation["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": body } },
+        });
+    }
+
+    operation
+}
+
+/// the openapi document of the local server, without the routes a read only server skips
+pub fn resolve_openapi_document(read_only: bool) -> Value {
+    let mut paths = Map::new();
+
+    let routes = routes()
+        .into_iter()
+        .filter(|route| !read_only || !TREE_CHANGING_PATHS.contains(&route.path));
+
+    for route in routes {
+        let item = paths
+            .entry(route.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        item[route.method] = route_to_operation(&route);
+    }
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": "wz local server",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        // the port is chosen at startup, so the paths are relative to where it is served
+        "servers": [{ "url": "/" }],
+        "paths": paths,
+        "components": {
+            "schemas": component_schemas(),
+            "securitySchemes": {
+                "tokenHeader": { "type": "apiKey", "in": "header", "name": TOKEN_HEADER },
+                "tokenQuery": { "type": "apiKey", "in": "query", "name": TOKEN_QUERY },
+            },
+        },
+        // the token from `get_server_url`, either one is enough
+        "security": [{ "tokenHeader": [] }, { "tokenQuery": [] }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+    use wz_reader::property::WzSubProperty;
+    use wz_reader::{WzNode, WzObjectType};
+
+    use super::*;
+    use crate::cache::ResponseCache;
+    use crate::link_index::LinkIndex;
+    use crate::memory::ImageTracker;
+    use crate::server::{access::ServerAccess, router, ServerOptions, ServerState};
+
+    fn empty_state() -> ServerState {
+        let root = WzNode::from_str(
+            "Base",
+            WzObjectType::Property(WzSubProperty::Property),
+            None,
+        )
+        .into_lock();
+
+        ServerState {
+            node: root.clone(),
+            string_dict: Default::default(),
+            images: ImageTracker::new(root),
+            stats: Default::default(),
+            image_cache: ResponseCache::new(0),
+            pack_sources: Default::default(),
+            link_index: LinkIndex::new(),
+            access: ServerAccess::new(),
+            mount_overrides: Default::default(),
+            mount_skills: Default::default(),
+        }
+    }
+
+    /// the documented path with samples in its parameters
+    fn sample_uri(path: &str) -> String {
+        path.replace("{path}", "Skill/000.img/icon")
+            .replace("{id}", "1001003")
+            .replace("{format}", "webp")
+    }
+
+    async fn request_status(app: &axum::Router, method: &str, path: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method.to_uppercase().as_str())
+            .uri(sample_uri(path))
+            .body(Body::empty())
+            .unwrap();
+
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    fn documented_routes(read_only: bool) -> Vec<(String, String)> {
+        let document = resolve_openapi_document(read_only);
+
+        document["paths"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .flat_map(|(path, item)| {
+                item.as_object()
+                    .unwrap()
+                    .keys()
+                    .map(|method| (method.clone(), path.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn every_documented_route_is_served() {
+        for read_only in [false, true] {
+            let options = ServerOptions {
+                read_only,
+                ..ServerOptions::local(0)
+            };
+            let app = router(empty_state(), &options);
+
+            // the token is only checked on the matched routes, the others are 404 or 405
+            for (method, path) in documented_routes(read_only) {
+                let status = request_status(&app, &method, &path).await;
+                assert_eq!(
+                    status,
+                    StatusCode::UNAUTHORIZED,
+                    "{method} {path}, read only: {read_only}"
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn read_only_document_skips_the_tree_changing_routes() {
+        let options = ServerOptions {
+            read_only: true,
+            ..ServerOptions::local(0)
+        };
+        let app = router(empty_state(), &options);
+        let served = documented_routes(true);
+
+        let skipped = documented_routes(false)
+            .into_iter()
+            .filter(|route| !served.contains(route))
+            .collect::<Vec<_>>();
+        assert_eq!(skipped.len(), TREE_CHANGING_PATHS.len());
+
+        for (method, path) in skipped {
+            let status = request_status(&app, &method, &path).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{method} {path}");
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use wz_reader::{WzNodeArc, WzNodeCast};
+
+use crate::cache::ResponseCache;
+use crate::memory::{SharedImageTracker, NODE_ESTIMATE_BYTES};
+use crate::store::{StringDict, StringDictItem};
+
+pub const EQUIP_CATALOG_CACHE: &'static str = "equipCatalog";
+pub const PARSED_IMAGE_CACHE: &'static str = "parsedImage";
+pub const IMAGE_RESPONSE_CACHE: &'static str = "imageResponse";
+
+#[derive(Default)]
+struct RouteCounter {
+    count: u64,
+    errors: u64,
+    total: Duration,
+    max: Duration,
+}
+
+#[derive(Default)]
+struct CacheCounter {
+    hits: u64,
+    misses: u64,
+}
+
+/// request and cache counters since the app started
+pub struct RuntimeStats {
+    started: Instant,
+    routes: Mutex<HashMap<String, RouteCounter>>,
+    caches: Mutex<BTreeMap<&'static str, CacheCounter>>,
+}
+
+pub type SharedRuntimeStats = Arc<RuntimeStats>;
+
+impl Default for RuntimeStats {
+    fn default() -> Self {
+        RuntimeStats {
+            started: Instant::now(),
+            routes: Mutex::new(HashMap::new()),
+            caches: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl RuntimeStats {
+    pub fn record_request(&self, route: &str, elapsed: Duration, is_error: bool) {
+        let mut routes = self.routes.lock().unwrap();
+        let counter = routes.entry(route.to_string()).or_default();
+
+        counter.count += 1;
+        counter.total += elapsed;
+        counter.max = counter.max.max(elapsed);
+        if is_error {
+            counter.errors += 1;
+        }
+    }
+
+    pub fn record_cache(&self, cache: &'static str, is_hit: bool) {
+        let mut caches = self.caches.lock().unwrap();
+        let counter = caches.entry(cache).or_default();
+
+        if is_hit {
+            counter.hits += 1;
+        } else {
+            counter.misses += 1;
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RootFileStats {
+    pub name: String,
+    pub path: String,
+    pub patch_version: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryStats {
+    /// estimated from the node count of every parsed image
+    pub parsed_nodes_bytes: u64,
+    /// the equip catalog built by `/string/equip/prepare`
+    pub catalog_bytes: u64,
+    /// the images tracked by the memory budget
+    pub tracked_images: usize,
+    pub tracked_images_bytes: u64,
+    /// 0 means no limit
+    pub budget_bytes: u64,
+    pub image_cache_entries: usize,
+    pub image_cache_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteStats {
+    pub route: String,
+    pub count: u64,
+    pub errors: u64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub name: String,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsReport {
+    pub uptime_ms: u64,
+    pub files: Vec<RootFileStats>,
+    pub node_count: u64,
+    pub parsed_images: u64,
+    pub unparsed_images: u64,
+    pub memory: MemoryStats,
+    pub routes: Vec<RouteStats>,
+    pub caches: Vec<CacheStats>,
+}
+
+#[derive(Default)]
+struct TreeStats {
+    files: Vec<RootFileStats>,
+    node_count: u64,
+    parsed_images: u64,
+    unparsed_images: u64,
+    /// nodes inside parsed images
+    parsed_nodes: u64,
+}
+
+fn collect_tree_stats(node: &WzNodeArc, in_image: bool, stats: &mut TreeStats) {
+    let node_read = node.read().unwrap();
+
+    stats.node_count += 1;
+
+    if in_image {
+        stats.parsed_nodes += 1;
+    }
+
+    if let Some(file) = node_read.try_as_file() {
+        stats.files.push(RootFileStats {
+            name: node_read.name.to_string(),
+            path: file.wz_file_meta.path.clone(),
+            patch_version: file.wz_file_meta.patch_version,
+        });
+    }
+
+    let is_image = node_read.try_as_image().is_some();
+    if is_image {
+        if node_read.children.is_empty() {
+            stats.unparsed_images += 1;
+        } else {
+            stats.parsed_images += 1;
+        }
+    }
+
+    for child in node_read.children.values() {
+        collect_tree_stats(child, in_image || is_image, stats);
+    }
+}
+
+fn get_catalog_bytes(string_dict: &StringDict) -> u64 {
+    string_dict
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(_, id, name, ..)| {
+            (std::mem::size_of::<StringDictItem>() + id.capacity() + name.capacity()) as u64
+        })
+        .sum()
+}
+
+fn to_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// walk the whole tree, it may take a while on a large client
+pub fn resolve_stats(
+    root: &WzNodeArc,
+    string_dict: &StringDict,
+    images: &SharedImageTracker,
+    image_cache: &ResponseCache,
+    runtime: &RuntimeStats,
+) -> StatsReport {
+    let mut tree = TreeStats::default();
+    collect_tree_stats(root, false, &mut tree);
+
+    let (tracked_images, tracked_images_bytes) = images.get_usage();
+    let (image_cache_entries, image_cache_bytes) = image_cache.get_usage();
+
+    let mut routes = runtime
+        .routes
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(route, counter)| RouteStats {
+            route: route.clone(),
+            count: counter.count,
+            errors: counter.errors,
+            avg_ms: to_ms(counter.total) / counter.count.max(1) as f64,
+            max_ms: to_ms(counter.max),
+        })
+        .collect::<Vec<_>>();
+    routes.sort_by(|a, b| a.route.cmp(&b.route));
+
+    let caches = runtime
+        .caches
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(name, counter)| CacheStats {
+            name: name.to_string(),
+            hits: counter.hits,
+            misses: counter.misses,
+            hit_rate: counter.hits as f64 / (counter.hits + counter.misses).max(1) as f64,
+        })
+        .collect();
+
+    StatsReport {
+        uptime_ms: runtime.started.elapsed().as_millis() as u64,
+        files: tree.files,
+        node_count: tree.node_count,
+        parsed_images: tree.parsed_images,
+        unparsed_images: tree.unparsed_images,
+        memory: MemoryStats {
+            parsed_nodes_bytes: tree.parsed_nodes * NODE_ESTIMATE_BYTES,
+            catalog_bytes: get_catalog_bytes(string_dict),
+            tracked_images,
+            tracked_images_bytes,
+            budget_bytes: images.get_budget(),
+            image_cache_entries,
+            image_cache_bytes: image_cache_bytes as u64,
+        },
+        routes,
+        caches,
+    }
+}
@@ -1,21 +1,72 @@
+use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
-use wz_reader::util::resolve_base;
-use wz_reader::version::WzMapleVersion;
-use wz_reader::{property::WzValue, WzNodeArc, WzObjectType};
+#[cfg(feature = "desktop")]
+use wz_reader::{
+    property::WzValue, util::resolve_base, version::WzMapleVersion, WzNodeArc, WzObjectType,
+};
 
-use crate::handlers::EquipCategory;
+use crate::handlers::{EquipCategory, MountSkillMap};
+#[cfg(feature = "desktop")]
+use crate::{
+    cache::SharedResponseCache, jobs::JobRegistry, link_index::SharedLinkIndex,
+    memory::SharedImageTracker, server::access::SharedServerAccess, stats::SharedRuntimeStats,
+    utils::LoadReport,
+};
 
 /* Category, Id, Name, isCash, isColor, hasEffect, isNameTag, isChatBalloon  */
 pub type StringDictItem = (EquipCategory, String, String, bool, bool, bool);
 pub type StringDictInner = Vec<StringDictItem>;
 pub type StringDict = Arc<RwLock<StringDictInner>>;
 
+// image path -> pack file name, for images loaded from Packs/*.ms
+pub type PackSources = Arc<RwLock<HashMap<String, String>>>;
+
+/* mount id -> skill id, from the user edited overrides file */
+pub type MountSkillOverrides = Arc<RwLock<HashMap<String, String>>>;
+
+/* the mount skills scanned from the loaded root, the overrides are applied on read */
+pub type MountSkillCache = Arc<RwLock<Option<Arc<MountSkillMap>>>>;
+
+pub fn get_pack_source(pack_sources: &PackSources, path: &str) -> Option<String> {
+    let pack_sources = pack_sources.read().unwrap();
+    let mut prefix = String::new();
+
+    for segment in path.split('/') {
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix.push_str(segment);
+        if let Some(pack) = pack_sources.get(&prefix) {
+            return Some(pack.clone());
+        }
+    }
+
+    None
+}
+
+/// the state of tauri commands, the standalone server does not need it
+#[cfg(feature = "desktop")]
 pub struct AppStore {
     pub node: WzNodeArc,
     pub string: StringDict,
     pub port: u16,
+    pub jobs: JobRegistry,
+    /// the report of last successful init
+    pub load_report: RwLock<Option<LoadReport>>,
+    pub pack_sources: PackSources,
+    /// parsed images and the memory budget
+    pub images: SharedImageTracker,
+    pub stats: SharedRuntimeStats,
+    /// encoded images of `/node/image`
+    pub image_cache: SharedResponseCache,
+    pub link_index: SharedLinkIndex,
+    /// the session token and allowed origins of the local server
+    pub access: SharedServerAccess,
+    pub mount_overrides: MountSkillOverrides,
+    pub mount_skills: MountSkillCache,
 }
+#[cfg(feature = "desktop")]
 impl AppStore {
     pub fn is_empty(&self) -> bool {
         matches!(
@@ -26,9 +77,17 @@ impl AppStore {
     pub fn replace_root(&self, another: &WzNodeArc) {
         let mut node = self.node.write().unwrap();
         std::mem::swap(&mut *node, &mut *another.write().unwrap());
+        self.images.clear();
+        self.image_cache.clear();
+        self.link_index.clear();
+        *self.mount_skills.write().unwrap() = None;
+    }
+    /// find the pack which the node at path or its parent image came from
+    pub fn get_pack_source(&self, path: &str) -> Option<String> {
+        get_pack_source(&self.pack_sources, path)
     }
     pub fn init_root(&self, path: &str, version: Option<WzMapleVersion>) -> crate::Result<()> {
-        let root = resolve_base(path, version).map_err(|_| crate::Error::InitWzFailed)?;
+        let root = resolve_base(path, version)?;
 
         self.replace_root(&root);
 
@@ -1,4 +1,4 @@
-use tauri::async_runtime::spawn_blocking;
+use tokio::task::spawn_blocking;
 use wz_reader::{util::node_util::parse_node, WzNodeArc};
 
 use crate::{Error, Result};
@@ -8,7 +8,7 @@ pub async fn block_parse(node: &WzNodeArc) -> Result<()> {
 
     spawn_blocking(move || parse_node(&node))
         .await
-        .map_err(|_| Error::InitWzFailed)?
+        .map_err(|e| Error::Io(e.into()))?
         .map_err(Error::from)
 }
 
@@ -18,6 +18,6 @@ pub async fn block_parse_with_parent(node: &WzNodeArc, parent: &WzNodeArc) -> Re
 
     spawn_blocking(move || node.write().unwrap().parse(&parent))
         .await
-        .map_err(|_| Error::InitWzFailed)?
+        .map_err(|e| Error::Io(e.into()))?
         .map_err(Error::from)
 }
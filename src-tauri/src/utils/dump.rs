@@ -0,0 +1,354 @@
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::DynamicImage;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use tokio::task::spawn_blocking;
+use wz_reader::{
+    property::{Vector2D, WzString, WzSubProperty, WzValue},
+    WzNode, WzNodeArc, WzObjectType,
+};
+
+use super::{LoadFileKind, SharedLoadReporter};
+use crate::{Error, Result};
+
+/// the dump canvas is a property node, the png is keep in this child as base64
+pub const DUMP_CANVAS_KEY: &'static str = "_canvas";
+/// the dump sound is a property node, the sound data is keep in this child as base64
+pub const DUMP_SOUND_KEY: &'static str = "_sound";
+
+fn new_folder_node(name: &str, parent: Option<&WzNodeArc>) -> WzNodeArc {
+    WzNode::from_str(
+        name,
+        WzObjectType::Property(WzSubProperty::Property),
+        parent,
+    )
+    .into_lock()
+}
+
+fn insert_child(parent: &WzNodeArc, node: WzNodeArc) {
+    let name = node.read().unwrap().name.clone();
+    parent.write().unwrap().children.insert(name, node);
+}
+
+fn get_or_create_folder(parent: &WzNodeArc, name: &str) -> WzNodeArc {
+    if let Some(folder) = parent.read().unwrap().at(name) {
+        return folder;
+    }
+
+    let folder = new_folder_node(name, Some(parent));
+    insert_child(parent, folder.clone());
+
+    folder
+}
+
+fn get_attribute(element: &BytesStart, key: &str) -> Result<Option<String>> {
+    for attr in element.attributes() {
+        let attr = attr.map_err(quick_xml::Error::from)?;
+        if attr.key.as_ref() == key.as_bytes() {
+            return Ok(Some(attr.unescape_value()?.to_string()));
+        }
+    }
+
+    Ok(None)
+}
+
+fn parse_attribute<T: std::str::FromStr + Default>(element: &BytesStart, key: &str) -> Result<T> {
+    Ok(get_attribute(element, key)?
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or_default())
+}
+
+/// build a node from the xml element, like `<int name="delay" value="90"/>`
+fn build_xml_node(element: &BytesStart, parent: &WzNodeArc) -> Result<WzNodeArc> {
+    let name = get_attribute(element, "name")?.unwrap_or_default();
+    let value = get_attribute(element, "value")?;
+
+    let object_type = match element.name().as_ref() {
+        b"short" => WzObjectType::Value(WzValue::Short(parse_attribute(element, "value")?)),
+        b"int" => WzObjectType::Value(WzValue::Int(parse_attribute(element, "value")?)),
+        b"long" => WzObjectType::Value(WzValue::Long(parse_attribute(element, "value")?)),
+        b"float" => WzObjectType::Value(WzValue::Float(parse_attribute(element, "value")?)),
+        b"double" => WzObjectType::Value(WzValue::Double(parse_attribute(element, "value")?)),
+        // the same as the strings of a .wz image, so `resolve_string_from_node` reads them
+        b"string" => WzObjectType::Value(WzValue::String(WzString::from_str(
+            &value.unwrap_or_default(),
+            [0, 0, 0, 0],
+        ))),
+        // keep the uol as it is, like the uol of an unparsed .wz image
+        b"uol" => WzObjectType::Value(WzValue::UOL(WzString::from_str(
+            &value.unwrap_or_default(),
+            [0, 0, 0, 0],
+        ))),
+        b"vector" => WzObjectType::Value(WzValue::Vector(Vector2D(
+            parse_attribute(element, "x")?,
+            parse_attribute(element, "y")?,
+        ))),
+        b"null" => WzObjectType::Value(WzValue::Null),
+        // imgdir, extended, canvas, sound
+        _ => WzObjectType::Property(WzSubProperty::Property),
+    };
+
+    let node = WzNode::from_str(&name, object_type, Some(parent)).into_lock();
+
+    let data_key = match element.name().as_ref() {
+        b"canvas" => Some(DUMP_CANVAS_KEY),
+        b"sound" => Some(DUMP_SOUND_KEY),
+        _ => None,
+    };
+
+    if let Some(data_key) = data_key {
+        if let Some(data) = get_attribute(element, "basedata")? {
+            let data_node = WzNode::from_str(
+                data_key,
+                WzObjectType::Value(WzValue::ParsedString(data)),
+                Some(&node),
+            );
+            insert_child(&node, data_node.into_lock());
+        }
+    }
+
+    Ok(node)
+}
+
+/// parse a HaRepacker xml dump into a image node
+fn parse_xml_image(path: &Path, name: &str, parent: &WzNodeArc) -> Result<WzNodeArc> {
+    let mut reader = Reader::from_file(path)?;
+    reader.config_mut().trim_text(true);
+
+    let image_node = new_folder_node(name, Some(parent));
+    let mut stack: Vec<WzNodeArc> = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            // the first imgdir is the image itself
+            Event::Start(_) if stack.is_empty() => stack.push(image_node.clone()),
+            Event::Start(element) => {
+                let current = stack.last().unwrap();
+                let node = build_xml_node(&element, current)?;
+                insert_child(current, node.clone());
+                stack.push(node);
+            }
+            Event::Empty(element) if !stack.is_empty() => {
+                let current = stack.last().unwrap();
+                let node = build_xml_node(&element, current)?;
+                insert_child(current, node);
+            }
+            Event::End(_) => {
+                stack.pop();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(image_node)
+}
+
+fn load_dump_dir(
+    dir: &Path,
+    parent: &WzNodeArc,
+    iv: Option<[u8; 4]>,
+    reporter: &SharedLoadReporter,
+) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let path_str = path.to_string_lossy().to_string();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+
+        if path.is_dir() {
+            // HaRepacker export folder like Skill.wz
+            let folder = get_or_create_folder(parent, file_name.trim_end_matches(".wz"));
+            load_dump_dir(&path, &folder, iv, reporter)?;
+            continue;
+        }
+
+        let started = Instant::now();
+
+        if file_name.ends_with(".img") {
+            reporter.loading(&path_str);
+            let node = match iv {
+                Some(iv) => WzNode::from_img_file_with_iv(&path_str, iv, Some(parent)),
+                None => WzNode::from_img_file(&path_str, None, Some(parent)),
+            };
+            match node {
+                Ok(node) => {
+                    insert_child(parent, node.into_lock());
+                    reporter.loaded(&path_str, LoadFileKind::Img, started);
+                }
+                Err(e) => reporter.failed(&path_str, &e),
+            }
+        } else if file_name.ends_with(".xml") {
+            reporter.loading(&path_str);
+            let name = file_name.trim_end_matches(".xml");
+            match parse_xml_image(&path, name, parent) {
+                Ok(node) => {
+                    insert_child(parent, node);
+                    reporter.loaded(&path_str, LoadFileKind::Xml, started);
+                }
+                Err(e) => reporter.failed(&path_str, &e),
+            }
+        } else {
+            reporter.skipped(&path_str, "not a .img or .xml file");
+        }
+    }
+
+    Ok(())
+}
+
+/// build the wz tree from a folder of `.img` files or HaRepacker/HaCreator xml dumps,
+/// the `.img` files guess the iv themselves when `iv` is None
+pub async fn resolve_dump(
+    dir: &str,
+    iv: Option<[u8; 4]>,
+    reporter: SharedLoadReporter,
+) -> Result<WzNodeArc> {
+    let dir_path = Path::new(dir).to_path_buf();
+
+    if !dir_path.is_dir() {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "not a directory",
+        )));
+    }
+
+    spawn_blocking(move || {
+        let root = new_folder_node("Base", None);
+
+        load_dump_dir(&dir_path, &root, iv, &reporter)?;
+
+        Ok(root)
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))?
+}
+
+fn decode_dump_data(node: &WzNode, key: &str) -> Option<Result<Vec<u8>>> {
+    let data_node = node.at(key)?;
+    let data_read = data_node.read().unwrap();
+
+    let WzObjectType::Value(WzValue::ParsedString(data)) = &data_read.object_type else {
+        return None;
+    };
+
+    Some(
+        STANDARD
+            .decode(data.trim())
+            .map_err(|e| Error::ImageProcessingError(e.to_string())),
+    )
+}
+
+/// decode the canvas of dump, None if the node is not a dump canvas
+pub fn resolve_dump_canvas(node: &WzNode) -> Option<Result<DynamicImage>> {
+    let data = decode_dump_data(node, DUMP_CANVAS_KEY)?;
+
+    Some(data.and_then(|data| {
+        image::load_from_memory(&data).map_err(|e| Error::ImageProcessingError(e.to_string()))
+    }))
+}
+
+/// get the sound data of dump, None if the node is not a dump sound
+pub fn resolve_dump_sound(node: &WzNode) -> Option<Result<Vec<u8>>> {
+    decode_dump_data(node, DUMP_SOUND_KEY)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{ImageFormat, RgbaImage};
+    use wz_reader::property::resolve_string_from_node;
+
+    use super::*;
+    use crate::handlers;
+    use crate::utils::LoadReporter;
+
+    fn encode_canvas(width: u32, height: u32) -> String {
+        let mut buf = Cursor::new(Vec::new());
+        DynamicImage::ImageRgba8(RgbaImage::new(width, height))
+            .write_to(&mut buf, ImageFormat::Png)
+            .unwrap();
+        STANDARD.encode(buf.into_inner())
+    }
+
+    fn write_dump(dir: &Path, path: &str, body: &str) {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            path,
+            format!("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n{body}"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn resolves_strings_and_outlinks_of_xml_dump() {
+        let dir = std::env::temp_dir().join(format!("maple-lens-dump-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        write_dump(
+            &dir,
+            "String.wz/Skill.img.xml",
+            r#"<imgdir name="Skill.img">
+                <imgdir name="1001003">
+                    <string name="name" value="Iron Body"/>
+                </imgdir>
+            </imgdir>"#,
+        );
+        write_dump(
+            &dir,
+            "Skill.wz/100.img.xml",
+            &format!(
+                r#"<imgdir name="100.img">
+                    <imgdir name="skill">
+                        <imgdir name="1001003">
+                            <canvas name="icon" width="1" height="1" basedata="{}">
+                                <string name="_outlink" value="Skill/000.img/icon/0"/>
+                            </canvas>
+                        </imgdir>
+                    </imgdir>
+                </imgdir>"#,
+                encode_canvas(1, 1)
+            ),
+        );
+        write_dump(
+            &dir,
+            "Skill.wz/000.img.xml",
+            &format!(
+                r#"<imgdir name="000.img">
+                    <imgdir name="icon">
+                        <canvas name="0" width="2" height="3" basedata="{}"/>
+                    </imgdir>
+                </imgdir>"#,
+                encode_canvas(2, 3)
+            ),
+        );
+
+        let root = new_folder_node("Base", None);
+        let reporter = LoadReporter::silent(&dir.to_string_lossy());
+        let loaded = load_dump_dir(&dir, &root, None, &reporter);
+        let _ = fs::remove_dir_all(&dir);
+        loaded.unwrap();
+
+        let name = root
+            .read()
+            .unwrap()
+            .at_path("String/Skill.img/1001003/name")
+            .unwrap();
+        assert_eq!(resolve_string_from_node(&name).unwrap(), "Iron Body");
+
+        let icon =
+            handlers::resolve_png_form_root(&root, "Skill/100.img/skill/1001003/icon").unwrap();
+        assert_eq!((icon.width(), icon.height()), (2, 3));
+    }
+}
@@ -0,0 +1,236 @@
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use wz_reader::{
+    util::{node_util, WzMutableKey},
+    version::{get_iv_by_maple_version, WzMapleVersion},
+    SharedWzMutableKey, WzNode, WzNodeArc, WzNodeCast,
+};
+
+use super::{block_parse, get_wz_root_path};
+use crate::{Error, Result};
+
+/// region name -> wz version, most of the newer regions are not encrypted
+const REGION_PRESETS: [(&str, WzMapleVersion); 10] = [
+    ("GMS", WzMapleVersion::GMS),
+    ("EMS", WzMapleVersion::EMS),
+    ("MSEA", WzMapleVersion::EMS),
+    ("BMS", WzMapleVersion::BMS),
+    ("KMS", WzMapleVersion::BMS),
+    ("JMS", WzMapleVersion::BMS),
+    ("CMS", WzMapleVersion::BMS),
+    ("TMS", WzMapleVersion::BMS),
+    // most of the private servers are based on old GMS
+    ("PRIVATE", WzMapleVersion::GMS),
+    ("PRIVATE_SERVER", WzMapleVersion::GMS),
+];
+
+/// the distinct keys to try when detecting, ordered by how common they are
+const AUTO_DETECT_CANDIDATES: [(&str, WzMapleVersion); 3] = [
+    ("GMS", WzMapleVersion::GMS),
+    ("BMS", WzMapleVersion::BMS),
+    ("MSEA", WzMapleVersion::EMS),
+];
+
+/// how many string nodes to check before giving up finding a sample
+const SAMPLE_STRING_LIMIT: usize = 64;
+
+pub enum WzKeySelection {
+    Preset(&'static str, WzMapleVersion),
+    Custom([u8; 4]),
+    Auto,
+}
+
+#[derive(Default)]
+pub struct ResolvedWzKey {
+    pub version: Option<WzMapleVersion>,
+    pub keys: Option<SharedWzMutableKey>,
+    /// the iv of the `.img` files in a dump
+    pub iv: Option<[u8; 4]>,
+    /// the preset name or iv in hex which is used
+    pub label: Option<String>,
+}
+
+/// parse iv like `4D23C72B`, `0x4D23C72B` or `4D 23 C7 2B`
+pub fn parse_iv_hex(text: &str) -> Option<[u8; 4]> {
+    let hex = text
+        .trim()
+        .trim_start_matches("0x")
+        .trim_start_matches("0X")
+        .replace([' ', '-', ','], "");
+
+    // the slicing below is by byte, so only ascii is allowed
+    if hex.len() != 8 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let mut iv = [0; 4];
+    for (i, byte) in iv.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+
+    Some(iv)
+}
+
+pub fn format_iv_hex(iv: &[u8; 4]) -> String {
+    iv.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// accept a region preset, `AUTO` or a custom iv in hex
+pub fn parse_key_selection(text: &str) -> Result<WzKeySelection> {
+    let upper = text.trim().to_uppercase();
+
+    if upper == "AUTO" {
+        return Ok(WzKeySelection::Auto);
+    }
+
+    if let Some((name, version)) = REGION_PRESETS.iter().find(|(name, _)| *name == upper) {
+        return Ok(WzKeySelection::Preset(name, *version));
+    }
+
+    parse_iv_hex(&upper)
+        .map(WzKeySelection::Custom)
+        .ok_or(Error::InvalidWzKey(text.to_string()))
+}
+
+fn is_readable(text: &str) -> bool {
+    !text.is_empty()
+        && !text.contains('\u{FFFD}')
+        && text.chars().all(|c| !c.is_control() || c.is_whitespace())
+}
+
+fn find_sample_string(node: &WzNodeArc, checked: &mut usize) -> Option<String> {
+    let node_read = node.read().unwrap();
+
+    if let Some(string) = node_read.try_as_string() {
+        *checked += 1;
+        return string.get_string().ok();
+    }
+
+    for child in node_read.children.values() {
+        if *checked >= SAMPLE_STRING_LIMIT {
+            return None;
+        }
+        if let Some(text) = find_sample_string(child, checked) {
+            return Some(text);
+        }
+    }
+
+    None
+}
+
+/// check the String.wz decode readable image names and a readable sample string
+async fn is_key_matched(string_wz_path: &str, version: WzMapleVersion) -> bool {
+    let node: WzNodeArc =
+        match WzNode::from_wz_file_full(string_wz_path, Some(version), None, None, None) {
+            Ok(node) => node.into(),
+            Err(_) => return false,
+        };
+
+    if block_parse(&node).await.is_err() {
+        return false;
+    }
+
+    let image = {
+        let node_read = node.read().unwrap();
+        let has_readable_name = node_read
+            .children
+            .keys()
+            .any(|name| name.ends_with(".img") && is_readable(name));
+
+        if !has_readable_name {
+            return false;
+        }
+
+        node_read
+            .children
+            .values()
+            .find(|child| child.read().unwrap().try_as_image().is_some())
+            .cloned()
+    };
+
+    // newer String.wz only has the directory, the images are in String_nnn.wz
+    let Some(image) = image else {
+        return true;
+    };
+
+    if node_util::parse_node(&image).is_err() {
+        return false;
+    }
+
+    find_sample_string(&image, &mut 0).map_or(true, |text| is_readable(&text))
+}
+
+fn get_string_wz_path(base_path: &str) -> Option<String> {
+    let wz_root_path = get_wz_root_path(Path::new(base_path));
+
+    [
+        wz_root_path.join("String").join("String.wz"),
+        wz_root_path.join("String.wz"),
+    ]
+    .into_iter()
+    .find(|path| path.exists())
+    .and_then(|path| path.to_str().map(String::from))
+}
+
+/// try every candidate key against String.wz, return the first one decode readable text
+pub async fn detect_wz_key(base_path: &str) -> Result<(&'static str, WzMapleVersion)> {
+    let string_wz_path = get_string_wz_path(base_path).ok_or(Error::InvalidWzKey(
+        "String.wz not found, can't detect the key".to_string(),
+    ))?;
+
+    for (name, version) in AUTO_DETECT_CANDIDATES {
+        if is_key_matched(&string_wz_path, version).await {
+            return Ok((name, version));
+        }
+    }
+
+    Err(Error::InvalidWzKey(
+        "none of the known keys can decode String.wz".to_string(),
+    ))
+}
+
+pub async fn resolve_wz_key(selection: WzKeySelection, base_path: &str) -> Result<ResolvedWzKey> {
+    let resolved = match selection {
+        WzKeySelection::Preset(name, version) => ResolvedWzKey {
+            version: Some(version),
+            keys: None,
+            iv: None,
+            label: Some(name.to_string()),
+        },
+        WzKeySelection::Custom(iv) => ResolvedWzKey {
+            version: None,
+            keys: Some(Arc::new(RwLock::new(WzMutableKey::from_iv(iv)))),
+            iv: None,
+            label: Some(format_iv_hex(&iv)),
+        },
+        WzKeySelection::Auto => {
+            let (name, version) = detect_wz_key(base_path).await?;
+            ResolvedWzKey {
+                version: Some(version),
+                keys: None,
+                iv: None,
+                label: Some(format!("{} (auto detected)", name)),
+            }
+        }
+    };
+
+    Ok(resolved)
+}
+
+/// a dump has no String.wz to detect from, with `AUTO` every `.img` guesses its own iv
+pub fn resolve_dump_key(selection: WzKeySelection) -> ResolvedWzKey {
+    match selection {
+        WzKeySelection::Preset(name, version) => ResolvedWzKey {
+            iv: Some(get_iv_by_maple_version(version)),
+            label: Some(name.to_string()),
+            ..Default::default()
+        },
+        WzKeySelection::Custom(iv) => ResolvedWzKey {
+            iv: Some(iv),
+            label: Some(format_iv_hex(&iv)),
+            ..Default::default()
+        },
+        WzKeySelection::Auto => ResolvedWzKey::default(),
+    }
+}
@@ -0,0 +1,151 @@
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LoadFileKind {
+    Wz,
+    Ms,
+    Img,
+    Xml,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LoadStatus {
+    Loading,
+    Loaded,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedFile {
+    pub path: String,
+    pub kind: LoadFileKind,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnloadedFile {
+    pub path: String,
+    /// the reason of skipped, or the underlying error of failed
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadReport {
+    pub path: String,
+    /// the region preset or iv used to decrypt
+    pub key: Option<String>,
+    pub patch_version: i32,
+    pub elapsed_ms: u64,
+    pub loaded: Vec<LoadedFile>,
+    pub skipped: Vec<UnloadedFile>,
+    pub failed: Vec<UnloadedFile>,
+}
+
+/// payload of every progress event while loading
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadProgress {
+    pub path: String,
+    pub status: LoadStatus,
+    pub loaded: usize,
+    pub failed: usize,
+}
+
+pub type LoadProgressCallback = Box<dyn Fn(&LoadProgress) + Send + Sync>;
+
+/// collect the load report and notify the progress while resolving wz files
+pub struct LoadReporter {
+    report: Mutex<LoadReport>,
+    started: Instant,
+    on_progress: Option<LoadProgressCallback>,
+}
+
+pub type SharedLoadReporter = Arc<LoadReporter>;
+
+impl LoadReporter {
+    pub fn new(path: &str, on_progress: Option<LoadProgressCallback>) -> SharedLoadReporter {
+        Arc::new(LoadReporter {
+            report: Mutex::new(LoadReport {
+                path: path.to_string(),
+                ..Default::default()
+            }),
+            started: Instant::now(),
+            on_progress,
+        })
+    }
+
+    /// a reporter only collect the report without notify
+    pub fn silent(path: &str) -> SharedLoadReporter {
+        Self::new(path, None)
+    }
+
+    fn notify(&self, path: &str, status: LoadStatus) {
+        if let Some(on_progress) = &self.on_progress {
+            let (loaded, failed) = {
+                let report = self.report.lock().unwrap();
+                (report.loaded.len(), report.failed.len())
+            };
+            on_progress(&LoadProgress {
+                path: path.to_string(),
+                status,
+                loaded,
+                failed,
+            });
+        }
+    }
+
+    pub fn loading(&self, path: &str) {
+        self.notify(path, LoadStatus::Loading);
+    }
+
+    pub fn loaded(&self, path: &str, kind: LoadFileKind, started: Instant) {
+        self.report.lock().unwrap().loaded.push(LoadedFile {
+            path: path.to_string(),
+            kind,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        });
+        self.notify(path, LoadStatus::Loaded);
+    }
+
+    pub fn skipped(&self, path: &str, reason: impl ToString) {
+        self.report.lock().unwrap().skipped.push(UnloadedFile {
+            path: path.to_string(),
+            reason: reason.to_string(),
+        });
+        self.notify(path, LoadStatus::Skipped);
+    }
+
+    /// a path is only reported once, the first error is the most specific one
+    pub fn failed(&self, path: &str, error: impl ToString) {
+        {
+            let mut report = self.report.lock().unwrap();
+            if report.failed.iter().any(|file| file.path == path) {
+                return;
+            }
+            report.failed.push(UnloadedFile {
+                path: path.to_string(),
+                reason: error.to_string(),
+            });
+        }
+        self.notify(path, LoadStatus::Failed);
+    }
+
+    pub fn set_key(&self, key: Option<String>) {
+        self.report.lock().unwrap().key = key;
+    }
+
+    pub fn finish(&self, patch_version: i32) -> LoadReport {
+        let mut report = self.report.lock().unwrap().clone();
+        report.patch_version = patch_version;
+        report.elapsed_ms = self.started.elapsed().as_millis() as u64;
+        report
+    }
+}
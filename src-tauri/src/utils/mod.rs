@@ -2,4 +2,13 @@ pub mod resolver;
 pub use resolver::*;
 
 pub mod block_parse;
-pub use block_parse::*;
\ No newline at end of file
+pub use block_parse::*;
+
+pub mod load_report;
+pub use load_report::*;
+
+pub mod dump;
+pub use dump::*;
+
+pub mod keys;
+pub use keys::*;
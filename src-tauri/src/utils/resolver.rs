@@ -1,14 +1,16 @@
 use futures::future::{BoxFuture, FutureExt};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::fs::{self, DirEntry};
 
 use wz_reader::{
-    util::maple_crypto_constants, version::WzMapleVersion, SharedWzMutableKey, WzNode, WzNodeArc,
-    WzNodeCast,
+    property::WzSubProperty, util::maple_crypto_constants, version::WzMapleVersion,
+    SharedWzMutableKey, WzNode, WzNodeArc, WzNodeCast, WzObjectType,
 };
 
-use super::{block_parse, block_parse_with_parent};
+use super::{block_parse, block_parse_with_parent, LoadFileKind, LoadReporter, SharedLoadReporter};
+use crate::store::PackSources;
 use crate::{Error, Result};
 
 const WZ_ROOT_FOLDER_NEED_LOAD: [&str; 9] = [
@@ -23,6 +25,25 @@ const WZ_ROOT_FOLDER_NEED_LOAD: [&str; 9] = [
     "Sound",
 ];
 
+/// options that change how the wz tree is loaded
+#[derive(Clone, Default)]
+pub struct LoadOptions {
+    /// pack families to load from `Packs/*.ms`, like `Skill` or `Character`, None means every pack
+    pub pack_families: Option<Vec<String>>,
+    /// record which pack each grafted image came from
+    pub pack_sources: PackSources,
+    /// custom keys for Base.wz, will be reused by other wz files
+    pub keys: Option<SharedWzMutableKey>,
+}
+
+impl LoadOptions {
+    pub fn is_pack_family_enabled(&self, family: &str) -> bool {
+        self.pack_families
+            .as_ref()
+            .map_or(true, |families| families.iter().any(|f| f.eq_ignore_ascii_case(family)))
+    }
+}
+
 fn iv_to_version(iv: &[u8; 4]) -> Option<WzMapleVersion> {
     match iv {
         &maple_crypto_constants::WZ_GMSIV => Some(WzMapleVersion::GMS),
@@ -32,6 +53,19 @@ fn iv_to_version(iv: &[u8; 4]) -> Option<WzMapleVersion> {
     }
 }
 
+/// get the folder contains all wz from the Base.wz path
+pub fn get_wz_root_path(base_path: &Path) -> &Path {
+    let first_parent = base_path.parent().unwrap();
+
+    // if wz in /Base/Base.wz then it newer structure
+    if first_parent.file_stem().unwrap() == "Base" {
+        first_parent.parent().unwrap()
+    // assume it older structure, which is something like Maplestory/Base.wz
+    } else {
+        first_parent
+    }
+}
+
 pub async fn get_root_wz_file_path(dir: &DirEntry) -> Option<String> {
     let dir_name = dir.file_name();
     let mut inner_wz_name = dir_name.to_str().unwrap().to_string();
@@ -51,19 +85,36 @@ pub fn resolve_root_wz_file_dir<'a>(
     patch_version: Option<i32>,
     parent: Option<WzNodeArc>,
     default_keys: Option<SharedWzMutableKey>,
+    reporter: SharedLoadReporter,
 ) -> BoxFuture<'a, Result<WzNodeArc>> {
     async move {
-        let root_node: WzNodeArc = WzNode::from_wz_file_full(
+        let started = Instant::now();
+        reporter.loading(&dir);
+
+        let root_node: WzNodeArc = match WzNode::from_wz_file_full(
             &dir,
             version,
             patch_version,
             parent.as_ref(),
             default_keys.as_ref(),
-        )?
-        .into();
+        ) {
+            Ok(node) => node.into(),
+            Err(e) => {
+                reporter.failed(&dir, &e);
+                return Err(e.into());
+            }
+        };
         let wz_dir = Path::new(&dir).parent().unwrap();
+        // in the older layout the folder is shared by every root wz, like Maplestory/Character.wz,
+        // the other files there are loaded by `load_wz_by_base` instead of ignored
+        let is_own_folder = wz_dir.file_name() == Path::new(&dir).file_stem();
 
-        block_parse(&root_node).await?;
+        if let Err(e) = block_parse(&root_node).await {
+            reporter.failed(&dir, &e);
+            return Err(e);
+        }
+
+        reporter.loaded(&dir, LoadFileKind::Wz, started);
 
         {
             let mut entries = fs::read_dir(wz_dir).await?;
@@ -80,58 +131,70 @@ pub fn resolve_root_wz_file_dir<'a>(
                 if file_type.is_dir() && target_node.is_some() {
                     if let Some(file_path) = get_root_wz_file_path(&entry).await {
                         let dir_node = resolve_root_wz_file_dir(
-                            file_path,
+                            file_path.clone(),
                             version,
                             patch_version,
                             Some(Arc::clone(&root_node)),
                             default_keys.clone(),
+                            reporter.clone(),
                         )
                         .await;
 
-                        if let Ok(dir_node) = dir_node {
-                            /* replace the original one */
-                            let mut root_node_write = root_node.write().unwrap();
-                            root_node_write
-                                .children
-                                .insert(name.to_str().unwrap().into(), dir_node);
+                        match dir_node {
+                            Ok(dir_node) => {
+                                /* replace the original one */
+                                let mut root_node_write = root_node.write().unwrap();
+                                root_node_write
+                                    .children
+                                    .insert(name.to_str().unwrap().into(), dir_node);
+                            }
+                            Err(e) => reporter.failed(&file_path, &e),
                         }
                     }
                 } else if file_type.is_file() {
                     //  check is XXX_nnn.wz
                     let file_path = entry.path();
+                    let file_path_str = file_path.to_str().unwrap();
                     let file_name = file_path.file_stem().unwrap().to_str().unwrap();
+                    let is_wz_file = file_path.extension().map_or(false, |ext| ext == "wz");
 
                     let splited = file_name.split('_').collect::<Vec<&str>>();
 
-                    if splited.len() < 2 {
+                    if splited.len() < 2 || splited.last().unwrap().parse::<u16>().is_err() {
+                        if is_own_folder && is_wz_file && file_path_str != dir {
+                            reporter.skipped(file_path_str, "not a XXX_nnn.wz split file");
+                        }
                         continue;
                     }
 
-                    if splited.last().unwrap().parse::<u16>().is_err() {
-                        continue;
-                    }
+                    let started = Instant::now();
+                    reporter.loading(file_path_str);
 
-                    let node = WzNode::from_wz_file_full(
-                        file_path.to_str().unwrap(),
+                    let node = match WzNode::from_wz_file_full(
+                        file_path_str,
                         version,
                         patch_version,
                         None,
                         default_keys.as_ref(),
-                    );
-
-                    if node.is_err() {
-                        continue;
-                    }
-
-                    let node = node.unwrap().into_lock();
+                    ) {
+                        Ok(node) => node.into_lock(),
+                        Err(e) => {
+                            reporter.failed(file_path_str, &e);
+                            continue;
+                        }
+                    };
 
-                    if block_parse_with_parent(&node, &root_node).await.is_ok() {
-                        let mut node_write = node.write().unwrap();
-                        let mut root_node_write = root_node.write().unwrap();
-                        root_node_write.children.reserve(node_write.children.len());
-                        for (name, child) in node_write.children.drain() {
-                            root_node_write.children.insert(name, child);
+                    match block_parse_with_parent(&node, &root_node).await {
+                        Ok(_) => {
+                            let mut node_write = node.write().unwrap();
+                            let mut root_node_write = root_node.write().unwrap();
+                            root_node_write.children.reserve(node_write.children.len());
+                            for (name, child) in node_write.children.drain() {
+                                root_node_write.children.insert(name, child);
+                            }
+                            reporter.loaded(file_path_str, LoadFileKind::Wz, started);
                         }
+                        Err(e) => reporter.failed(file_path_str, &e),
                     }
                 }
             }
@@ -147,10 +210,14 @@ pub async fn load_wz_by_base(
     folders: &[&str],
     _: Option<WzMapleVersion>,
     path: Option<&str>,
+    reporter: SharedLoadReporter,
 ) -> Result<()> {
     let (patch_version, keys, path) = {
         let node_read = base_node.read().unwrap();
-        let file = node_read.try_as_file().unwrap();
+        // the root from dump is not a wz file, nothing to load
+        let file = node_read
+            .try_as_file()
+            .ok_or(Error::NodeTypeMismatch("WzFile"))?;
         let root_path = if let Some(p) = path {
             p.to_string()
         } else {
@@ -165,15 +232,7 @@ pub async fn load_wz_by_base(
     };
     let version = iv_to_version(&keys.read().unwrap().iv);
 
-    let first_parent = Path::new(&path).parent().unwrap();
-
-    // if wz in /Base/Base.wz then it newer structure
-    let wz_root_path = if first_parent.file_stem().unwrap() == "Base" {
-        first_parent.parent().unwrap()
-    // assume it older structure, which is something like Maplestory/Base.wz
-    } else {
-        first_parent
-    };
+    let wz_root_path = get_wz_root_path(Path::new(&path));
 
     let mut entries = fs::read_dir(wz_root_path).await?;
 
@@ -198,6 +257,10 @@ pub async fn load_wz_by_base(
 
         let is_need_load = folders.contains(&file_name.to_str().unwrap());
 
+        if has_dir && is_valid && !is_need_load {
+            reporter.skipped(path.to_str().unwrap(), "not in the load list");
+        }
+
         if has_dir && is_valid && is_need_load {
             // let wz_path = get_root_wz_file_path(&item).await;
             let wz_path = if item.file_type().await?.is_dir() {
@@ -213,13 +276,24 @@ pub async fn load_wz_by_base(
                     Some(patch_version),
                     Some(Arc::clone(&base_node)),
                     Some(Arc::clone(&keys)),
+                    reporter.clone(),
                 ));
+            } else {
+                reporter.skipped(path.to_str().unwrap(), "root wz file not found in folder");
             }
         }
     }
 
+    // a failed folder is already recorded by the reporter, keep loading the others
     while let Some(result) = set.join_next().await {
-        let node = result.unwrap()?;
+        let node = match result {
+            Ok(Ok(node)) => node,
+            Ok(Err(_)) => continue,
+            Err(e) => {
+                reporter.failed(wz_root_path.to_str().unwrap(), &e);
+                continue;
+            }
+        };
         let name = {
             let node_read = node.read().unwrap();
             node_read.name.clone()
@@ -230,7 +304,59 @@ pub async fn load_wz_by_base(
     Ok(())
 }
 
-pub async fn resolve_pack_ms(path: &str, base_node: &WzNodeArc) -> Result<()> {
+/// the family of a pack file, like `Skill_00001.ms` -> `Skill`, `Character2.ms` -> `Character`
+pub fn get_pack_family(file_name: &str) -> &str {
+    let stem = file_name.split('.').next().unwrap_or(file_name);
+    let family = stem.split('_').next().unwrap_or(stem);
+
+    family.trim_end_matches(|c: char| c.is_ascii_digit())
+}
+
+/// insert the pack entry at `path`, create the missing folders along the way.
+/// an existing node at `path` is replaced, the pack is the newer data
+fn graft_pack_node(root: &WzNodeArc, path: &str, node: &WzNodeArc) -> Option<WzNodeArc> {
+    let mut segments = path.split('/').filter(|s| !s.is_empty()).collect::<Vec<_>>();
+    let filename = segments.pop()?;
+
+    let mut dest_node = root.clone();
+
+    for segment in segments {
+        let child = dest_node.read().unwrap().at(segment);
+        dest_node = match child {
+            Some(child) => child,
+            None => {
+                let folder = WzNode::from_str(
+                    segment,
+                    WzObjectType::Property(WzSubProperty::Property),
+                    Some(&dest_node),
+                )
+                .into_lock();
+                dest_node
+                    .write()
+                    .unwrap()
+                    .children
+                    .insert(segment.into(), folder.clone());
+                folder
+            }
+        };
+    }
+
+    node.write().unwrap().parent = Arc::downgrade(&dest_node);
+    dest_node
+        .write()
+        .unwrap()
+        .children
+        .insert(filename.into(), node.clone());
+
+    Some(dest_node)
+}
+
+pub async fn resolve_pack_ms(
+    path: &str,
+    base_node: &WzNodeArc,
+    options: &LoadOptions,
+    reporter: SharedLoadReporter,
+) -> Result<()> {
     let first_parent = Path::new(&path).parent().unwrap();
     // the Pack folder only exist in newer structure
     if first_parent.file_stem().unwrap() != "Base" {
@@ -249,39 +375,74 @@ pub async fn resolve_pack_ms(path: &str, base_node: &WzNodeArc) -> Result<()> {
     let mut entries = pack_entry.unwrap();
 
     while let Some(item) = entries.next_entry().await? {
-        if !item.file_name().to_str().unwrap().starts_with("Skill") {
+        let item_path = item.path();
+        let item_path_str = item_path.to_str().unwrap();
+        let file_name = item.file_name().to_str().unwrap().to_string();
+
+        if item_path.extension().map_or(true, |ext| ext != "ms") {
             continue;
         }
 
-        let ms_node = WzNode::from_ms_file(item.path(), Some(base_node))?.into_lock();
-
-        block_parse(&ms_node).await?;
+        if !options.is_pack_family_enabled(get_pack_family(&file_name)) {
+            reporter.skipped(item_path_str, "pack family is disabled in setting");
+            continue;
+        }
 
-        let root_read = base_node.read().unwrap();
+        let started = Instant::now();
+        reporter.loading(item_path_str);
 
-        for (path, node) in ms_node.read().unwrap().children.iter() {
-            let p = Path::new(path.as_str());
-            let path_without_file = p.parent().unwrap();
-            let filename = p.file_name().unwrap().to_str().unwrap();
-            let dest_node = root_read.at_path(path_without_file.to_str().unwrap());
-            if dest_node.is_none() {
+        let ms_node = match WzNode::from_ms_file(item.path(), Some(base_node)) {
+            Ok(node) => node.into_lock(),
+            Err(e) => {
+                reporter.failed(item_path_str, &e);
                 continue;
             }
-            let dest_node = dest_node.unwrap();
-
-            node.write().unwrap().parent = Arc::downgrade(&dest_node);
-            dest_node
-                .write()
-                .unwrap()
-                .children
-                .insert(filename.into(), node.clone());
+        };
+
+        if let Err(e) = block_parse(&ms_node).await {
+            reporter.failed(item_path_str, &e);
+            continue;
+        }
+
+        let pack_entries = ms_node
+            .read()
+            .unwrap()
+            .children
+            .iter()
+            .map(|(path, node)| (path.replace('\\', "/"), node.clone()))
+            .collect::<Vec<_>>();
+
+        let mut pack_sources = options.pack_sources.write().unwrap();
+
+        for (path, node) in pack_entries {
+            if graft_pack_node(base_node, &path, &node).is_some() {
+                // also replaces the source of an image an earlier pack overrode
+                pack_sources.insert(path.trim_matches('/').to_string(), file_name.clone());
+            }
         }
+
+        reporter.loaded(item_path_str, LoadFileKind::Ms, started);
     }
 
     Ok(())
 }
 
 pub async fn resolve_base(path: &str, version: Option<WzMapleVersion>) -> Result<WzNodeArc> {
+    resolve_base_with_report(
+        path,
+        version,
+        &LoadOptions::default(),
+        LoadReporter::silent(path),
+    )
+    .await
+}
+
+pub async fn resolve_base_with_report(
+    path: &str,
+    version: Option<WzMapleVersion>,
+    options: &LoadOptions,
+    reporter: SharedLoadReporter,
+) -> Result<WzNodeArc> {
     if !path.ends_with("Base.wz") {
         return Err(Error::Io(std::io::Error::new(
             std::io::ErrorKind::InvalidInput,
@@ -289,17 +450,26 @@ pub async fn resolve_base(path: &str, version: Option<WzMapleVersion>) -> Result
         )));
     }
 
-    let base_node = resolve_root_wz_file_dir(path.to_string(), version, None, None, None).await?;
+    let base_node = resolve_root_wz_file_dir(
+        path.to_string(),
+        version,
+        None,
+        None,
+        options.keys.clone(),
+        reporter.clone(),
+    )
+    .await?;
 
     load_wz_by_base(
         base_node.clone(),
         &WZ_ROOT_FOLDER_NEED_LOAD,
         version,
         Some(path),
+        reporter.clone(),
     )
     .await?;
 
-    resolve_pack_ms(path, &base_node).await?;
+    resolve_pack_ms(path, &base_node, options, reporter).await?;
 
     Ok(base_node)
 }
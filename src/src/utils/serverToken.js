@@ -0,0 +1,17 @@
+// src/utils/serverToken.js
+// 本地服务器的会话 token，由 get_server_url 提供，每个请求都必须带上
+
+let serverToken = '';
+
+export const setServerToken = (token) => {
+  serverToken = token || '';
+};
+
+/**
+ * 给本地服务器的 URL 加上 token 参数，fetch 和 <img src> 都可以直接使用
+ */
+export const withToken = (url) => {
+  if (!serverToken) return url;
+  const separator = url.includes('?') ? '&' : '?';
+  return `${url}${separator}token=${encodeURIComponent(serverToken)}`;
+};